@@ -2,6 +2,17 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20241223_085007_dept_table;
 mod m20241223_085012_emp_table;
+mod m20261018_090000_emp_mgr_fk;
+mod m20261018_090100_salgrade_table;
+mod m20261018_090200_version_column;
+mod m20261018_090300_deleted_at_column;
+mod m20261018_090400_audit_log_table;
+mod m20261018_090500_history_table;
+mod m20261018_090600_search_index;
+mod m20261018_090700_constraints;
+mod m20261018_090800_salgrade_exclusion;
+mod m20261018_090900_active_reference_lock;
+mod m20261018_091000_history_clock_timestamp;
 
 pub struct Migrator;
 
@@ -11,6 +22,17 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20241223_085007_dept_table::Migration),
             Box::new(m20241223_085012_emp_table::Migration),
+            Box::new(m20261018_090000_emp_mgr_fk::Migration),
+            Box::new(m20261018_090100_salgrade_table::Migration),
+            Box::new(m20261018_090200_version_column::Migration),
+            Box::new(m20261018_090300_deleted_at_column::Migration),
+            Box::new(m20261018_090400_audit_log_table::Migration),
+            Box::new(m20261018_090500_history_table::Migration),
+            Box::new(m20261018_090600_search_index::Migration),
+            Box::new(m20261018_090700_constraints::Migration),
+            Box::new(m20261018_090800_salgrade_exclusion::Migration),
+            Box::new(m20261018_090900_active_reference_lock::Migration),
+            Box::new(m20261018_091000_history_clock_timestamp::Migration),
         ]
     }
 }
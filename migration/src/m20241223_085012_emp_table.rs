@@ -54,7 +54,7 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(DeriveIden)]
-enum Emp {
+pub enum Emp {
     Table,
     Empno,
     Ename,
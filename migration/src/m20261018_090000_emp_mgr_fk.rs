@@ -0,0 +1,35 @@
+use crate::m20241223_085012_emp_table::Emp;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // emp.mgr -> emp.empno（自己参照）
+        // NOTE: 存在しない mgr を参照しているレコードがある場合は失敗する
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_mgr")
+                    .from(Emp::Table, Emp::Mgr)
+                    .to(Emp::Table, Emp::Empno)
+                    .on_delete(ForeignKeyAction::Restrict)
+                    .on_update(ForeignKeyAction::Restrict)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_mgr")
+                    .table(Emp::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
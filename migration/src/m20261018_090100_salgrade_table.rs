@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Salgrade::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Salgrade::Grade)
+                            .integer()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Salgrade::Losal).decimal_len(7, 2).not_null())
+                    .col(ColumnDef::new(Salgrade::Hisal).decimal_len(7, 2).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Salgrade::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Salgrade {
+    Table,
+    Grade,
+    Losal,
+    Hisal,
+}
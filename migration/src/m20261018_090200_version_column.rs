@@ -0,0 +1,63 @@
+use crate::m20241223_085007_dept_table::Dept;
+use crate::m20241223_085012_emp_table::Emp;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 楽観ロック用のバージョン（更新ごとにインクリメント）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Dept::Table)
+                    .add_column(
+                        ColumnDef::new(Version::Version)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Emp::Table)
+                    .add_column(
+                        ColumnDef::new(Version::Version)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Emp::Table)
+                    .drop_column(Version::Version)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Dept::Table)
+                    .drop_column(Version::Version)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Version {
+    Version,
+}
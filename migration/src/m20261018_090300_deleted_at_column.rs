@@ -0,0 +1,61 @@
+use crate::m20241223_085007_dept_table::Dept;
+use crate::m20241223_085012_emp_table::Emp;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 論理削除日時（null: 未削除）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Dept::Table)
+                    .add_column(
+                        ColumnDef::new(DeletedAt::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Emp::Table)
+                    .add_column(
+                        ColumnDef::new(DeletedAt::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Emp::Table)
+                    .drop_column(DeletedAt::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Dept::Table)
+                    .drop_column(DeletedAt::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DeletedAt {
+    DeletedAt,
+}
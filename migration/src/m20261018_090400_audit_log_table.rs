@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditLog::Id)
+                            .integer()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuditLog::Entity).string_len(10).not_null())
+                    .col(ColumnDef::new(AuditLog::EntityKey).integer().not_null())
+                    .col(
+                        ColumnDef::new(AuditLog::Operation)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AuditLog::Before).json_binary().null())
+                    .col(ColumnDef::new(AuditLog::After).json_binary().null())
+                    .col(ColumnDef::new(AuditLog::RequestId).string_len(64).null())
+                    .col(ColumnDef::new(AuditLog::UserId).string_len(64).null())
+                    .col(
+                        ColumnDef::new(AuditLog::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_log_entity_key")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::Entity)
+                    .col(AuditLog::EntityKey)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_log_created_at")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AuditLog {
+    Table,
+    Id,
+    Entity,
+    EntityKey,
+    Operation,
+    Before,
+    After,
+    RequestId,
+    UserId,
+    CreatedAt,
+}
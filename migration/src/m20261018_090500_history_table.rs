@@ -0,0 +1,193 @@
+use sea_orm_migration::prelude::*;
+
+/// 履歴の記録（トリガー）
+///
+/// 更新・削除時は現在の履歴の valid_to を設定し、登録・更新時は新しい履歴を追加する.
+/// 有効期間は [valid_from, valid_to)（valid_to が null の履歴が現在の状態）
+const HISTORY_TRIGGER_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION dept_history_trigger() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE dept_history SET valid_to = now()
+        WHERE deptno = OLD.deptno AND valid_to IS NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO dept_history (deptno, dname, loc, version, deleted_at, valid_from)
+        VALUES (NEW.deptno, NEW.dname, NEW.loc, NEW.version, NEW.deleted_at, now());
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_dept_history
+    AFTER INSERT OR UPDATE OR DELETE ON dept
+    FOR EACH ROW EXECUTE FUNCTION dept_history_trigger();
+
+CREATE OR REPLACE FUNCTION emp_history_trigger() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE emp_history SET valid_to = now()
+        WHERE empno = OLD.empno AND valid_to IS NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO emp_history (empno, ename, job, mgr, hiredate, sal, comm, deptno, version, deleted_at, valid_from)
+        VALUES (NEW.empno, NEW.ename, NEW.job, NEW.mgr, NEW.hiredate, NEW.sal, NEW.comm, NEW.deptno, NEW.version, NEW.deleted_at, now());
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_emp_history
+    AFTER INSERT OR UPDATE OR DELETE ON emp
+    FOR EACH ROW EXECUTE FUNCTION emp_history_trigger();
+"#;
+
+/// 既存レコードの履歴（マイグレーション時点から有効とする）
+const HISTORY_SEED_SQL: &str = r#"
+INSERT INTO dept_history (deptno, dname, loc, version, deleted_at, valid_from)
+SELECT deptno, dname, loc, version, deleted_at, now() FROM dept;
+
+INSERT INTO emp_history (empno, ename, job, mgr, hiredate, sal, comm, deptno, version, deleted_at, valid_from)
+SELECT empno, ename, job, mgr, hiredate, sal, comm, deptno, version, deleted_at, now() FROM emp;
+"#;
+
+const DROP_HISTORY_TRIGGER_SQL: &str = r#"
+DROP TRIGGER IF EXISTS trg_emp_history ON emp;
+DROP FUNCTION IF EXISTS emp_history_trigger();
+DROP TRIGGER IF EXISTS trg_dept_history ON dept;
+DROP FUNCTION IF EXISTS dept_history_trigger();
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DeptHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DeptHistory::HistoryId)
+                            .integer()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(DeptHistory::Deptno).integer().not_null())
+                    .col(ColumnDef::new(DeptHistory::Dname).string_len(14).not_null())
+                    .col(ColumnDef::new(DeptHistory::Loc).string_len(13).not_null())
+                    .col(ColumnDef::new(DeptHistory::Version).integer().not_null())
+                    .col(ColumnDef::new(DeptHistory::DeletedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(DeptHistory::ValidFrom)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(DeptHistory::ValidTo).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_dept_history_deptno_valid")
+                    .table(DeptHistory::Table)
+                    .col(DeptHistory::Deptno)
+                    .col(DeptHistory::ValidFrom)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmpHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EmpHistory::HistoryId)
+                            .integer()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EmpHistory::Empno).integer().not_null())
+                    .col(ColumnDef::new(EmpHistory::Ename).string_len(10).not_null())
+                    .col(ColumnDef::new(EmpHistory::Job).string_len(9).not_null())
+                    .col(ColumnDef::new(EmpHistory::Mgr).integer())
+                    .col(ColumnDef::new(EmpHistory::Hiredate).date().not_null())
+                    .col(ColumnDef::new(EmpHistory::Sal).decimal_len(7, 2).not_null())
+                    .col(ColumnDef::new(EmpHistory::Comm).decimal_len(7, 2))
+                    .col(ColumnDef::new(EmpHistory::Deptno).integer().not_null())
+                    .col(ColumnDef::new(EmpHistory::Version).integer().not_null())
+                    .col(ColumnDef::new(EmpHistory::DeletedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(EmpHistory::ValidFrom)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(EmpHistory::ValidTo).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_emp_history_empno_valid")
+                    .table(EmpHistory::Table)
+                    .col(EmpHistory::Empno)
+                    .col(EmpHistory::ValidFrom)
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(HISTORY_SEED_SQL).await?;
+        db.execute_unprepared(HISTORY_TRIGGER_SQL).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_HISTORY_TRIGGER_SQL)
+            .await?;
+        manager
+            .drop_table(Table::drop().table(EmpHistory::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(DeptHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum DeptHistory {
+    Table,
+    HistoryId,
+    Deptno,
+    Dname,
+    Loc,
+    Version,
+    DeletedAt,
+    ValidFrom,
+    ValidTo,
+}
+
+#[derive(DeriveIden)]
+pub enum EmpHistory {
+    Table,
+    HistoryId,
+    Empno,
+    Ename,
+    Job,
+    Mgr,
+    Hiredate,
+    Sal,
+    Comm,
+    Deptno,
+    Version,
+    DeletedAt,
+    ValidFrom,
+    ValidTo,
+}
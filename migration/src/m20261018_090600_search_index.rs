@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+/// 検索用インデックス（pg_trgm: 部分一致・類似度 / tsvector: 前方一致）
+const SEARCH_INDEX_SQL: &str = r#"
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_emp_ename_trgm ON emp USING gin (lower(ename) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_emp_job_trgm ON emp USING gin (lower(job) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_emp_tsv ON emp USING gin (to_tsvector('simple', ename || ' ' || job));
+
+CREATE INDEX IF NOT EXISTS idx_dept_dname_trgm ON dept USING gin (lower(dname) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_dept_loc_trgm ON dept USING gin (lower(loc) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_dept_tsv ON dept USING gin (to_tsvector('simple', dname || ' ' || loc));
+"#;
+
+const DROP_SEARCH_INDEX_SQL: &str = r#"
+DROP INDEX IF EXISTS idx_dept_tsv;
+DROP INDEX IF EXISTS idx_dept_loc_trgm;
+DROP INDEX IF EXISTS idx_dept_dname_trgm;
+DROP INDEX IF EXISTS idx_emp_tsv;
+DROP INDEX IF EXISTS idx_emp_job_trgm;
+DROP INDEX IF EXISTS idx_emp_ename_trgm;
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // NOTE: pg_trgm の作成にはデータベースの CREATE 権限が必要
+        manager
+            .get_connection()
+            .execute_unprepared(SEARCH_INDEX_SQL)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_SEARCH_INDEX_SQL)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+/// 制約（一意・チェック）
+///
+/// dept(dname, loc) の一意制約は論理削除されたレコードを対象外とする
+const CONSTRAINT_SQL: &str = r#"
+CREATE UNIQUE INDEX IF NOT EXISTS uq_dept_dname_loc ON dept (dname, loc) WHERE deleted_at IS NULL;
+
+ALTER TABLE emp ADD CONSTRAINT ck_emp_sal CHECK (sal > 0);
+ALTER TABLE emp ADD CONSTRAINT ck_emp_comm CHECK (comm IS NULL OR comm >= 0);
+ALTER TABLE emp ADD CONSTRAINT ck_emp_mgr_not_self CHECK (mgr IS NULL OR mgr <> empno);
+ALTER TABLE salgrade ADD CONSTRAINT ck_salgrade_range CHECK (losal <= hisal);
+"#;
+
+/// 論理削除を考慮した参照制約（制約トリガー）
+///
+/// 外部キー（fk_deptno・fk_mgr）は物理的な参照のみを保証するため、論理削除されたレコードへの参照をトリガーで禁止する.
+/// 同一トランザクション内の一時的な不整合（付け替え・一括削除の途中）を許容するため、検証はコミット時に行う（INITIALLY DEFERRED）.
+/// 違反時は外部キー違反（23503）として制約名 fk_emp_dept_active / fk_emp_mgr_active を返す
+const ACTIVE_REFERENCE_TRIGGER_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION emp_active_reference_check() RETURNS trigger AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        IF NOT EXISTS (SELECT 1 FROM dept WHERE deptno = NEW.deptno AND deleted_at IS NULL) THEN
+            RAISE EXCEPTION 'deptno [%] is not active.', NEW.deptno
+                USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_dept_active';
+        END IF;
+        IF NEW.mgr IS NOT NULL
+            AND NOT EXISTS (SELECT 1 FROM emp WHERE empno = NEW.mgr AND deleted_at IS NULL) THEN
+            RAISE EXCEPTION 'mgr [%] is not active.', NEW.mgr
+                USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_mgr_active';
+        END IF;
+    ELSIF EXISTS (SELECT 1 FROM emp WHERE mgr = NEW.empno AND deleted_at IS NULL) THEN
+        RAISE EXCEPTION 'empno [%] is mgr of active emp.', NEW.empno
+            USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_mgr_active';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER trg_emp_active_reference
+    AFTER INSERT OR UPDATE ON emp
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION emp_active_reference_check();
+
+CREATE OR REPLACE FUNCTION dept_active_reference_check() RETURNS trigger AS $$
+BEGIN
+    IF NEW.deleted_at IS NOT NULL
+        AND EXISTS (SELECT 1 FROM emp WHERE deptno = NEW.deptno AND deleted_at IS NULL) THEN
+        RAISE EXCEPTION 'deptno [%] is referenced by active emp.', NEW.deptno
+            USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_dept_active';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER trg_dept_active_reference
+    AFTER UPDATE ON dept
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION dept_active_reference_check();
+"#;
+
+const DROP_CONSTRAINT_SQL: &str = r#"
+DROP TRIGGER IF EXISTS trg_dept_active_reference ON dept;
+DROP FUNCTION IF EXISTS dept_active_reference_check();
+DROP TRIGGER IF EXISTS trg_emp_active_reference ON emp;
+DROP FUNCTION IF EXISTS emp_active_reference_check();
+
+ALTER TABLE salgrade DROP CONSTRAINT IF EXISTS ck_salgrade_range;
+ALTER TABLE emp DROP CONSTRAINT IF EXISTS ck_emp_mgr_not_self;
+ALTER TABLE emp DROP CONSTRAINT IF EXISTS ck_emp_comm;
+ALTER TABLE emp DROP CONSTRAINT IF EXISTS ck_emp_sal;
+
+DROP INDEX IF EXISTS uq_dept_dname_loc;
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // NOTE: 既存データが制約に違反している場合は失敗する（事前にデータを修正すること）
+        let db = manager.get_connection();
+        db.execute_unprepared(CONSTRAINT_SQL).await?;
+        db.execute_unprepared(ACTIVE_REFERENCE_TRIGGER_SQL).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_CONSTRAINT_SQL)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+/// 排他制約: salgrade の losal ~ hisal の範囲は重複しないこと（同時登録・更新の競合対策）
+const SALGRADE_EXCLUSION_SQL: &str = r#"
+ALTER TABLE salgrade ADD CONSTRAINT ex_salgrade_range
+    EXCLUDE USING gist (numrange(losal, hisal, '[]') WITH &&);
+"#;
+
+const DROP_SALGRADE_EXCLUSION_SQL: &str = r#"
+ALTER TABLE salgrade DROP CONSTRAINT IF EXISTS ex_salgrade_range;
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // NOTE: 既存データの範囲が重複している場合は失敗する（事前にデータを修正すること）
+        manager
+            .get_connection()
+            .execute_unprepared(SALGRADE_EXCLUSION_SQL)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_SALGRADE_EXCLUSION_SQL)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+/// 論理削除を考慮した参照制約（制約トリガー）の排他
+///
+/// 参照先（dept・mgr）の存在チェック時に行ロック（FOR SHARE）を取得し、チェック後・コミット前に参照先が論理削除されることを防ぐ.
+/// 論理削除（deleted_at の UPDATE）は FOR NO KEY UPDATE のため、FOR KEY SHARE では競合しない（FOR SHARE とする）
+const ACTIVE_REFERENCE_LOCK_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION emp_active_reference_check() RETURNS trigger AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        PERFORM 1 FROM dept WHERE deptno = NEW.deptno AND deleted_at IS NULL FOR SHARE;
+        IF NOT FOUND THEN
+            RAISE EXCEPTION 'deptno [%] is not active.', NEW.deptno
+                USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_dept_active';
+        END IF;
+        IF NEW.mgr IS NOT NULL THEN
+            PERFORM 1 FROM emp WHERE empno = NEW.mgr AND deleted_at IS NULL FOR SHARE;
+            IF NOT FOUND THEN
+                RAISE EXCEPTION 'mgr [%] is not active.', NEW.mgr
+                    USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_mgr_active';
+            END IF;
+        END IF;
+    ELSIF EXISTS (SELECT 1 FROM emp WHERE mgr = NEW.empno AND deleted_at IS NULL) THEN
+        RAISE EXCEPTION 'empno [%] is mgr of active emp.', NEW.empno
+            USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_mgr_active';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+"#;
+
+const DROP_ACTIVE_REFERENCE_LOCK_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION emp_active_reference_check() RETURNS trigger AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        IF NOT EXISTS (SELECT 1 FROM dept WHERE deptno = NEW.deptno AND deleted_at IS NULL) THEN
+            RAISE EXCEPTION 'deptno [%] is not active.', NEW.deptno
+                USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_dept_active';
+        END IF;
+        IF NEW.mgr IS NOT NULL
+            AND NOT EXISTS (SELECT 1 FROM emp WHERE empno = NEW.mgr AND deleted_at IS NULL) THEN
+            RAISE EXCEPTION 'mgr [%] is not active.', NEW.mgr
+                USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_mgr_active';
+        END IF;
+    ELSIF EXISTS (SELECT 1 FROM emp WHERE mgr = NEW.empno AND deleted_at IS NULL) THEN
+        RAISE EXCEPTION 'empno [%] is mgr of active emp.', NEW.empno
+            USING ERRCODE = 'foreign_key_violation', CONSTRAINT = 'fk_emp_mgr_active';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(ACTIVE_REFERENCE_LOCK_SQL)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_ACTIVE_REFERENCE_LOCK_SQL)
+            .await?;
+        Ok(())
+    }
+}
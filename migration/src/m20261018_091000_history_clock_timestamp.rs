@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+/// 履歴の記録（トリガー）の有効期間を書込時刻とする
+///
+/// now() はトランザクション開始時刻のため、長いトランザクションが後から更新した場合に有効期間が前後する.
+/// 行ロックで同一レコードの更新は直列化されるため、書込時刻（clock_timestamp()）であればレコードごとの有効期間は単調増加となる
+const HISTORY_CLOCK_TIMESTAMP_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION dept_history_trigger() RETURNS trigger AS $$
+DECLARE
+    changed_at timestamptz := clock_timestamp();
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE dept_history SET valid_to = changed_at
+        WHERE deptno = OLD.deptno AND valid_to IS NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO dept_history (deptno, dname, loc, version, deleted_at, valid_from)
+        VALUES (NEW.deptno, NEW.dname, NEW.loc, NEW.version, NEW.deleted_at, changed_at);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION emp_history_trigger() RETURNS trigger AS $$
+DECLARE
+    changed_at timestamptz := clock_timestamp();
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE emp_history SET valid_to = changed_at
+        WHERE empno = OLD.empno AND valid_to IS NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO emp_history (empno, ename, job, mgr, hiredate, sal, comm, deptno, version, deleted_at, valid_from)
+        VALUES (NEW.empno, NEW.ename, NEW.job, NEW.mgr, NEW.hiredate, NEW.sal, NEW.comm, NEW.deptno, NEW.version, NEW.deleted_at, changed_at);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+"#;
+
+const DROP_HISTORY_CLOCK_TIMESTAMP_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION dept_history_trigger() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE dept_history SET valid_to = now()
+        WHERE deptno = OLD.deptno AND valid_to IS NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO dept_history (deptno, dname, loc, version, deleted_at, valid_from)
+        VALUES (NEW.deptno, NEW.dname, NEW.loc, NEW.version, NEW.deleted_at, now());
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION emp_history_trigger() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE emp_history SET valid_to = now()
+        WHERE empno = OLD.empno AND valid_to IS NULL;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO emp_history (empno, ename, job, mgr, hiredate, sal, comm, deptno, version, deleted_at, valid_from)
+        VALUES (NEW.empno, NEW.ename, NEW.job, NEW.mgr, NEW.hiredate, NEW.sal, NEW.comm, NEW.deptno, NEW.version, NEW.deleted_at, now());
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+"#;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(HISTORY_CLOCK_TIMESTAMP_SQL)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_HISTORY_CLOCK_TIMESTAMP_SQL)
+            .await?;
+        Ok(())
+    }
+}
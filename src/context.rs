@@ -0,0 +1,122 @@
+use actix_web::{dev::Payload, web, FromRequest, HttpMessage, HttpRequest};
+use std::future::{ready, Ready};
+
+use crate::error::ApiCustomError;
+use crate::state::AppState;
+
+/// ヘッダ: ユーザID
+const USER_ID_HEADER: &str = "X-User-Id";
+
+/// ユーザIDの最大長（監査ログ audit_log.user_id の桁数）
+const USER_ID_MAX_LEN: usize = 64;
+
+/// ヘッダ: リクエストID
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// リクエストIDの最大長（監査ログ audit_log.request_id の桁数）
+pub const REQUEST_ID_MAX_LEN: usize = 64;
+
+/// ヘッダ: ユーザロール
+const USER_ROLE_HEADER: &str = "X-User-Role";
+
+/// ロール: 管理者
+const ADMIN_ROLE: &str = "admin";
+
+tokio::task_local! {
+    /// リクエストID（リクエスト処理中のタスクで参照する）
+    pub static REQUEST_ID: Option<String>;
+}
+
+/// 処理中のリクエストのリクエストID
+///
+/// リクエスト処理外（REQUEST_ID のスコープ外）の場合は None
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok().flatten()
+}
+
+/// 構造体: リクエストID（リクエストの extensions に設定する）
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// 構造体: リクエストコンテキスト（操作ユーザ）
+///
+/// 認証は前段（ゲートウェイ等）で行い、ユーザID・ロールをヘッダで受け取る.
+/// ヘッダはクライアントが任意に指定できるため、前段が X-User-Id・X-User-Role を上書き（クライアント指定値を除去）する構成の場合のみ
+/// TRUST_USER_HEADERS=true とすること. 無効（デフォルト）の場合はヘッダを無視する（ユーザ・ロールなし. 管理者操作は不可）
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    pub user_id: Option<String>,
+    pub role: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl RequestContext {
+    /// 管理者かどうか
+    pub fn is_admin(&self) -> bool {
+        self.role.as_deref() == Some(ADMIN_ROLE)
+    }
+}
+
+impl FromRequest for RequestContext {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let trust_user_headers = req
+            .app_data::<web::Data<AppState>>()
+            .is_some_and(|data| data.trust_user_headers);
+        let user_header = |name: &str| match trust_user_headers {
+            true => header(name),
+            false => None,
+        };
+        // ユーザIDの桁数チェック（監査ログに記録できないため 400）
+        let user_id = user_header(USER_ID_HEADER);
+        if let Some(user_id) = &user_id {
+            if user_id.chars().count() > USER_ID_MAX_LEN {
+                let mut length_error = validator::ValidationError::new("length");
+                length_error.add_param("min".into(), &1);
+                length_error.add_param("max".into(), &USER_ID_MAX_LEN);
+                let mut errors = validator::ValidationErrors::new();
+                errors.add(USER_ID_HEADER, length_error);
+                return ready(Err(ApiCustomError::from(errors).into()));
+            }
+        }
+        ready(Ok(RequestContext {
+            user_id,
+            role: user_header(USER_ROLE_HEADER),
+            request_id: req
+                .extensions()
+                .get::<RequestId>()
+                .map(|RequestId(id)| id.clone())
+                .or_else(|| header(REQUEST_ID_HEADER))
+                .filter(|id| id.len() <= REQUEST_ID_MAX_LEN),
+        }))
+    }
+}
+
+/// 構造体: 論理削除レコード取得クエリ
+#[derive(serde::Deserialize, Debug)]
+pub struct DeletedQuery {
+    // true の場合は論理削除されたレコードも含める（管理者のみ）
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+impl DeletedQuery {
+    /// 論理削除されたレコードを含めるかどうか
+    ///
+    /// 管理者以外が include_deleted=true を指定した場合は 403
+    pub fn include_deleted(&self, ctx: &RequestContext) -> Result<bool, ApiCustomError> {
+        if self.include_deleted && !ctx.is_admin() {
+            return Err(ApiCustomError::Forbidden);
+        }
+        Ok(self.include_deleted)
+    }
+}
@@ -0,0 +1,144 @@
+use actix_web::{http::header, web::Bytes, HttpResponse};
+use futures_util::{future, stream, StreamExt};
+use sea_orm::sea_query::ValueType;
+use sea_orm::{DatabaseConnection, EntityTrait, ModelTrait, Select};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::error::ApiCustomError;
+
+/// CSV取込のリクエストボディの上限（バイト. actix のデフォルト 256KiB では数千行で 413 となるため）
+pub const IMPORT_PAYLOAD_LIMIT: usize = 10 * 1024 * 1024;
+
+/// 構造体: CSV取込クエリ
+#[derive(serde::Deserialize, Debug)]
+pub struct ImportQuery {
+    // true の場合はチェックのみ（登録しない）
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 構造体: CSV取込結果
+#[derive(serde::Serialize, Debug)]
+pub struct ImportResultJson {
+    pub rows: usize,
+    pub dry_run: bool,
+}
+
+/// CSV読込（ヘッダ行あり）
+///
+/// 全行をデシリアライズ・バリデートし、エラーがある場合は行（ヘッダを除く0始まり）ごとのエラーを返却する
+pub fn read_csv<T>(body: &[u8]) -> Result<Vec<T>, ApiCustomError>
+where
+    T: DeserializeOwned + Validate,
+{
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body);
+
+    let mut rows = Vec::new();
+    let mut row_errors = Vec::new();
+    for (index, result) in reader.deserialize::<T>().enumerate() {
+        match result {
+            Ok(row) => match row.validate() {
+                Ok(_) => rows.push(row),
+                Err(errors) => row_errors.push((index, errors)),
+            },
+            Err(err) => {
+                let mut parse_error = validator::ValidationError::new("parse");
+                parse_error.add_param("reason".into(), &err.to_string());
+                let mut errors = validator::ValidationErrors::new();
+                errors.add("csv", parse_error);
+                row_errors.push((index, errors));
+            }
+        }
+    }
+
+    if !row_errors.is_empty() {
+        return Err(ApiCustomError::RowValidationError(row_errors));
+    }
+    if rows.is_empty() {
+        let mut errors = validator::ValidationErrors::new();
+        errors.add("csv", validator::ValidationError::new("no_rows"));
+        return Err(errors.into());
+    }
+    Ok(rows)
+}
+
+/// CSVエクスポートの1回あたりの取得件数
+const EXPORT_CHUNK_SIZE: u64 = 1000;
+
+/// CSVレスポンス（ダウンロード）
+///
+/// 全件をメモリに載せないよう、`key`のキーセットで`EXPORT_CHUNK_SIZE`件ずつ取得してチャンクで返却する.
+/// 返却開始後のDBエラーはレスポンスを中断する（ステータスは変更できないため）
+pub fn csv_response<E, F>(
+    conn: DatabaseConnection,
+    select: Select<E>,
+    key: E::Column,
+    filename: &str,
+    headers: &[&str],
+    to_record: F,
+) -> Result<HttpResponse, ApiCustomError>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    F: Fn(E::Model) -> Vec<String> + 'static,
+{
+    let header_row = write_records([headers.iter().map(|header| header.to_string()).collect()])?;
+
+    // 取得済みの最後のキー（None: 先頭から. 全件取得後はストリーム終了）
+    let chunks = stream::unfold(Some(None), move |after: Option<Option<i32>>| {
+        let conn = conn.clone();
+        let select = select.clone();
+        async move {
+            let after = after?;
+            let mut cursor = select.cursor_by(key);
+            if let Some(after) = after {
+                cursor.after(after);
+            }
+            let items = match cursor.first(EXPORT_CHUNK_SIZE).all(&conn).await {
+                Ok(items) => items,
+                Err(err) => return Some((Err(err), None)),
+            };
+            if items.is_empty() {
+                return None;
+            }
+            let next = match items.len() as u64 == EXPORT_CHUNK_SIZE {
+                true => items
+                    .last()
+                    .and_then(|item| <i32 as ValueType>::try_from(item.get(key)).ok())
+                    .map(Some),
+                false => None,
+            };
+            Some((Ok(items), next))
+        }
+    })
+    .map(move |chunk| {
+        chunk
+            .map_err(ApiCustomError::from)
+            .and_then(|items| write_records(items.into_iter().map(&to_record)))
+            .inspect_err(|err| tracing::error!(error = %err, "csv export aborted."))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        ))
+        .streaming(stream::once(future::ready(Ok(header_row))).chain(chunks)))
+}
+
+/// CSV書込（レコードのみ）
+fn write_records<I>(records: I) -> Result<Bytes, ApiCustomError>
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for record in records {
+        writer.write_record(&record).map_err(anyhow::Error::from)?;
+    }
+    let body = writer.into_inner().map_err(anyhow::Error::from)?;
+    Ok(Bytes::from(body))
+}
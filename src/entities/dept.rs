@@ -11,6 +11,10 @@ pub struct Model {
     pub deptno: i32,
     pub dname: String,
     pub loc: String,
+    #[serde(skip_deserializing)] // NOTE: using json
+    pub version: i32,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")] // NOTE: using json
+    pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, serde::Serialize, serde::Deserialize)]
+#[sea_orm(table_name = "dept_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub history_id: i32,
+    pub deptno: i32,
+    pub dname: String,
+    pub loc: String,
+    pub version: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub valid_from: DateTimeWithTimeZone,
+    pub valid_to: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -20,6 +20,10 @@ pub struct Model {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comm: Option<Decimal>,
     pub deptno: i32,
+    #[serde(skip_deserializing)] // NOTE: using json
+    pub version: i32,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")] // NOTE: using json
+    pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,6 +36,14 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     Dept,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::Mgr",
+        to = "Column::Empno",
+        on_update = "Restrict",
+        on_delete = "Restrict"
+    )]
+    SelfRef,
 }
 
 impl Related<super::dept::Entity> for Entity {
@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, serde::Serialize, serde::Deserialize)]
+#[sea_orm(table_name = "emp_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub history_id: i32,
+    pub empno: i32,
+    pub ename: String,
+    pub job: String,
+    pub mgr: Option<i32>,
+    pub hiredate: Date,
+    #[sea_orm(column_type = "Decimal(Some((7, 2)))")]
+    pub sal: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((7, 2)))", nullable)]
+    pub comm: Option<Decimal>,
+    pub deptno: i32,
+    pub version: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub valid_from: DateTimeWithTimeZone,
+    pub valid_to: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
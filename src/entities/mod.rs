@@ -2,5 +2,10 @@
 
 pub mod prelude;
 
+pub mod audit_log;
 pub mod dept;
+pub mod dept_history;
 pub mod emp;
+pub mod emp_history;
+pub mod ext;
+pub mod salgrade;
@@ -1,4 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+pub use super::audit_log::Entity as AuditLog;
 pub use super::dept::Entity as Dept;
 pub use super::emp::Entity as Emp;
+pub use super::salgrade::Entity as Salgrade;
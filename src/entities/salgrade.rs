@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, serde::Serialize, serde::Deserialize)]
+#[sea_orm(table_name = "salgrade")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    #[serde(skip_deserializing)] // NOTE: using json
+    pub grade: i32,
+    #[sea_orm(column_type = "Decimal(Some((7, 2)))")]
+    pub losal: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((7, 2)))")]
+    pub hisal: Decimal,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,12 @@
 use actix_web::{body::BoxBody, http::StatusCode, HttpResponse, ResponseError};
 
+use once_cell::sync::OnceCell;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::context::current_request_id;
+use crate::i18n;
+
 /// enum カスタムエラー
 #[derive(thiserror::Error, Debug)]
 pub enum ApiCustomError {
@@ -7,9 +14,29 @@ pub enum ApiCustomError {
     #[error("Not Found.")]
     NotFound,
 
-    /// 処理不可
-    #[error("Unporcessible Entity.")]
-    UnporcessibleEntity(String),
+    /// 権限なし
+    #[error("Forbidden.")]
+    Forbidden,
+
+    /// 処理不可（メッセージカタログのキー `error.unprocessable_entity.{0}` とパラメータ）
+    #[error("Unporcessible Entity. [{0}]")]
+    UnporcessibleEntity(&'static str, HashMap<Cow<'static, str>, serde_json::Value>),
+
+    /// 更新競合（If-Match 不一致）
+    #[error("Precondition Failed.")]
+    PreconditionFailed,
+
+    /// If-Match 未指定
+    #[error("Precondition Required.")]
+    PreconditionRequired,
+
+    /// 一意制約・排他制約違反（制約名）
+    #[error("Conflict. constraint [{0}] is violated.")]
+    Conflict(String),
+
+    /// 外部キー・チェック制約違反（制約名）
+    #[error("Unporcessible Entity. constraint [{0}] is violated.")]
+    ConstraintViolation(String),
 
     /// actix内部処理で発生したエラー
     #[error(transparent)]
@@ -19,19 +46,235 @@ pub enum ApiCustomError {
     #[error(transparent)]
     ValidationError(#[from] validator::ValidationErrors),
 
+    /// バリデートエラー（複数件. 要素のインデックスとエラー）
+    #[error("Bad Request.")]
+    RowValidationError(Vec<(usize, validator::ValidationErrors)>),
+
+    /// 要素のエラー（一括処理の要素のインデックスとエラー. ステータス・コードは元のエラーのまま）
+    #[error("[{0}] {1}")]
+    RowError(usize, Box<ApiCustomError>),
+
     // DB(SeaORM)エラー
     #[error(transparent)]
-    DbError(#[from] sea_orm::DbErr),
+    DbError(sea_orm::DbErr),
 
     /// その他
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
-/// 構造体: エラーレスポンスJSON
+/// DB(SeaORM)エラーの変換
+///
+/// 一意制約違反（23505）・排他制約違反（23P01）は Conflict、外部キー違反（23503）・チェック制約違反（23514）は ConstraintViolation とする
+impl From<sea_orm::DbErr> for ApiCustomError {
+    fn from(err: sea_orm::DbErr) -> Self {
+        use sea_orm::{sqlx, DbErr, RuntimeErr};
+
+        let violation = match &err {
+            DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(e)))
+            | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(e)))
+            | DbErr::Conn(RuntimeErr::SqlxError(sqlx::Error::Database(e))) => e
+                .code()
+                .zip(e.constraint())
+                .map(|(code, constraint)| (code.to_string(), constraint.to_string())),
+            _ => None,
+        };
+        match violation {
+            Some((code, constraint)) if code == "23505" || code == "23P01" => {
+                ApiCustomError::Conflict(constraint)
+            }
+            Some((code, constraint)) if code == "23503" || code == "23514" => {
+                ApiCustomError::ConstraintViolation(constraint)
+            }
+            _ => ApiCustomError::DbError(err),
+        }
+    }
+}
+
+/// エラーレスポンスの Content-Type（RFC 7807）
+const PROBLEM_JSON: &str = "application/problem+json";
+
+/// 構造体: エラーレスポンスJSON（RFC 7807 Problem Details）
 #[derive(serde::Serialize)]
-struct ErrorResponseJson {
-    message: String,
+struct ProblemJson {
+    // 問題の種類（固有の定義がないため about:blank）
+    #[serde(rename = "type")]
+    problem_type: &'static str,
+    // ステータスコードの説明
+    title: &'static str,
+    status: u16,
+    // エラーコード（機械判読用）
+    code: &'static str,
+    // 詳細メッセージ
+    detail: String,
+    // リクエストID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+    // 項目ごとのエラー（項目 -> [{code, message, params}]）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<serde_json::Map<String, serde_json::Value>>,
+    // エラーID（サーバーエラーの場合. ログと突き合わせる）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_id: Option<String>,
+}
+
+/// デバッグモード（サーバーエラーの詳細をレスポンスに含める）
+static DEBUG_MODE: OnceCell<bool> = OnceCell::new();
+
+/// デバッグモードの設定（起動時に1度だけ設定する）
+pub fn set_debug_mode(debug: bool) {
+    DEBUG_MODE.set(debug).ok();
+}
+
+/// デバッグモードかどうか（未設定の場合はプロダクションモード）
+fn is_debug_mode() -> bool {
+    DEBUG_MODE.get().copied().unwrap_or(false)
+}
+
+impl ApiCustomError {
+    /// エラーコード
+    fn code(&self) -> &'static str {
+        match self {
+            ApiCustomError::NotFound => "not_found",
+            ApiCustomError::Forbidden => "forbidden",
+            ApiCustomError::UnporcessibleEntity(..) => "unprocessable_entity",
+            ApiCustomError::PreconditionFailed => "precondition_failed",
+            ApiCustomError::PreconditionRequired => "precondition_required",
+            ApiCustomError::Conflict(_) => "conflict",
+            ApiCustomError::ConstraintViolation(_) => "constraint_violation",
+            ApiCustomError::ActixWebError(_) => match self.status_code() {
+                StatusCode::NOT_FOUND => "not_found",
+                _ => "bad_request",
+            },
+            ApiCustomError::ValidationError(_) => "validation_error",
+            ApiCustomError::RowValidationError(_) => "validation_error",
+            ApiCustomError::RowError(_, err) => err.code(),
+            ApiCustomError::DbError(_) => "db_error",
+            ApiCustomError::Other(_) => "internal_error",
+        }
+    }
+
+    /// 処理不可
+    ///
+    /// * `reason` - メッセージカタログのキー（`error.unprocessable_entity.{reason}`）
+    /// * `params` - メッセージのパラメータ
+    pub fn unprocessable<const N: usize>(
+        reason: &'static str,
+        params: [(&'static str, serde_json::Value); N],
+    ) -> Self {
+        ApiCustomError::UnporcessibleEntity(
+            reason,
+            params
+                .into_iter()
+                .map(|(name, value)| (Cow::Borrowed(name), value))
+                .collect(),
+        )
+    }
+
+    /// 詳細メッセージ（Accept-Language の言語）
+    ///
+    /// RowError は先頭に `[インデックス]` を付与する
+    fn detail(&self) -> String {
+        let param = |name: &'static str, value: String| {
+            HashMap::from([(Cow::Borrowed(name), serde_json::Value::String(value))])
+        };
+        let (code, params) = match self {
+            ApiCustomError::UnporcessibleEntity(reason, params) => {
+                return i18n::translate(&format!("error.unprocessable_entity.{}", reason), params)
+                    .unwrap_or_else(|| format!("{}", self));
+            }
+            ApiCustomError::RowError(index, err) => return format!("[{}] {}", index, err.detail()),
+            ApiCustomError::Conflict(constraint)
+            | ApiCustomError::ConstraintViolation(constraint) => {
+                (self.code(), param("constraint", constraint.to_string()))
+            }
+            ApiCustomError::ActixWebError(err) => (self.code(), param("reason", err.to_string())),
+            ApiCustomError::DbError(err) => ("internal_error", param("reason", err.to_string())),
+            ApiCustomError::Other(err) => ("internal_error", param("reason", err.to_string())),
+            _ => (self.code(), HashMap::new()),
+        };
+        i18n::translate(&format!("error.{}", code), &params).unwrap_or_else(|| format!("{}", self))
+    }
+
+    /// サーバーエラーのログ出力
+    ///
+    /// エラーID を採番し、エラーの原因（anyhow はバックトレースを含む）をすべて出力する
+    fn log_server_error(&self) -> String {
+        let error_id = uuid::Uuid::new_v4().to_string();
+        let error = match self {
+            ApiCustomError::Other(err) => format!("{:?}", err),
+            err => {
+                let mut chain = vec![err.to_string()];
+                let mut source = std::error::Error::source(err);
+                while let Some(err) = source {
+                    chain.push(err.to_string());
+                    source = err.source();
+                }
+                chain.join(" <- ")
+            }
+        };
+        tracing::error!(error_id, error);
+        error_id
+    }
+
+    /// 項目ごとのエラー
+    fn errors(&self) -> Option<serde_json::Map<String, serde_json::Value>> {
+        let mut errors = serde_json::Map::new();
+        match self {
+            ApiCustomError::ValidationError(err) => flatten_validation_errors("", err, &mut errors),
+            ApiCustomError::RowValidationError(rows) => {
+                for (index, err) in rows {
+                    flatten_validation_errors(&format!("[{}]", index), err, &mut errors);
+                }
+            }
+            ApiCustomError::RowError(_, err) => return err.errors(),
+            _ => return None,
+        }
+        Some(errors)
+    }
+}
+
+/// バリデートエラーを 項目 -> [{code, message, params}] に展開する
+///
+/// ネストした項目は `親.子`、リストの要素は `親[インデックス]` の形式とする
+fn flatten_validation_errors(
+    prefix: &str,
+    errors: &validator::ValidationErrors,
+    result: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    let join = |field: &str| match prefix {
+        "" => field.to_string(),
+        _ => format!("{}.{}", prefix, field),
+    };
+    for (field, kind) in errors.errors() {
+        match kind {
+            validator::ValidationErrorsKind::Field(field_errors) => {
+                let field_errors = field_errors
+                    .iter()
+                    .map(|err| {
+                        serde_json::json!({
+                            "code": err.code,
+                            "message": i18n::validation_message(field, err),
+                            "params": err.params,
+                        })
+                    })
+                    .collect();
+                result.insert(join(field), serde_json::Value::Array(field_errors));
+            }
+            validator::ValidationErrorsKind::Struct(errors) => {
+                flatten_validation_errors(&join(field), errors, result)
+            }
+            validator::ValidationErrorsKind::List(list) => {
+                for (index, errors) in list {
+                    flatten_validation_errors(
+                        &format!("{}[{}]", join(field), index),
+                        errors,
+                        result,
+                    )
+                }
+            }
+        }
+    }
 }
 
 /// カスタムエラー実装
@@ -40,50 +283,139 @@ impl ResponseError for ApiCustomError {
     fn status_code(&self) -> actix_web::http::StatusCode {
         match self {
             ApiCustomError::NotFound => StatusCode::NOT_FOUND,
-            ApiCustomError::UnporcessibleEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiCustomError::Forbidden => StatusCode::FORBIDDEN,
+            ApiCustomError::UnporcessibleEntity(..) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiCustomError::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            ApiCustomError::PreconditionRequired => StatusCode::PRECONDITION_REQUIRED,
+            ApiCustomError::Conflict(_) => StatusCode::CONFLICT,
+            ApiCustomError::ConstraintViolation(_) => StatusCode::UNPROCESSABLE_ENTITY,
             ApiCustomError::ActixWebError(err) => err.as_response_error().status_code(),
             ApiCustomError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ApiCustomError::RowValidationError(_) => StatusCode::BAD_REQUEST,
+            ApiCustomError::RowError(_, err) => err.status_code(),
             ApiCustomError::DbError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiCustomError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
-    /// エラーレスポンス
+    /// エラーレスポンス（application/problem+json）
+    ///
+    /// サーバーエラーはエラーID を付与してログを出力する.
+    /// プロダクションモードの場合、サーバーエラーの詳細（DB・内部処理のエラー内容）はレスポンスに含めない
     fn error_response(&self) -> HttpResponse<BoxBody> {
-        match self {
-            ApiCustomError::NotFound => {
-                HttpResponse::build(self.status_code()).json(ErrorResponseJson {
-                    message: format!("{}", self),
-                })
-            }
-            ApiCustomError::UnporcessibleEntity(message) => HttpResponse::build(self.status_code())
-                .json(ErrorResponseJson {
-                    message: message.to_string(),
-                }),
-            ApiCustomError::ActixWebError(err) => {
-                let message = match self.status_code() {
-                    StatusCode::NOT_FOUND => "Not Found.",
-                    _ => "Bad Request.",
-                };
-                HttpResponse::build(self.status_code()).json(ErrorResponseJson {
-                    message: format!("{} [{}]", message, err),
-                })
-            }
-            ApiCustomError::ValidationError(err) => {
-                HttpResponse::build(self.status_code()).json(ErrorResponseJson {
-                    message: format!("Bad Request. [{}]", err),
-                })
-            }
-            ApiCustomError::DbError(err) => {
-                HttpResponse::build(self.status_code()).json(ErrorResponseJson {
-                    message: format!("Internal Server Error. [{}]", err),
-                })
-            }
-            ApiCustomError::Other(err) => {
-                HttpResponse::build(self.status_code()).json(ErrorResponseJson {
-                    message: format!("Internal Server Error. [{}]", err),
-                })
-            }
-        }
+        let status = self.status_code();
+        let error_id = match status.is_server_error() {
+            true => Some(self.log_server_error()),
+            false => None,
+        };
+        let (code, detail) = match error_id.is_some() && !is_debug_mode() {
+            true => (
+                "internal_error",
+                i18n::translate("error.internal_error_hidden", &HashMap::new())
+                    .unwrap_or_else(|| "Internal Server Error.".to_string()),
+            ),
+            false => (self.code(), self.detail()),
+        };
+        HttpResponse::build(status)
+            .content_type(PROBLEM_JSON)
+            .json(ProblemJson {
+                problem_type: "about:blank",
+                title: status.canonical_reason().unwrap_or_default(),
+                status: status.as_u16(),
+                code,
+                detail,
+                instance: current_request_id(),
+                errors: self.errors(),
+                error_id,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Item {
+        #[validate(range(min = 1, max = 10))]
+        qty: i32,
+    }
+
+    #[derive(Validate)]
+    struct Form {
+        #[validate(length(min = 1, max = 5))]
+        code: String,
+        #[validate(nested)]
+        item: Item,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    fn flatten(form: &Form) -> serde_json::Map<String, serde_json::Value> {
+        let mut result = serde_json::Map::new();
+        flatten_validation_errors("", &form.validate().unwrap_err(), &mut result);
+        result
+    }
+
+    #[test]
+    fn flatten_validation_errors_field() {
+        let result = flatten(&Form {
+            code: "toolong".to_string(),
+            item: Item { qty: 1 },
+            items: Vec::new(),
+        });
+        assert_eq!(result.keys().collect::<Vec<_>>(), vec!["code"]);
+        let error = &result["code"][0];
+        assert_eq!(error["code"], "length");
+        assert_eq!(error["params"]["max"], 5);
+        assert_eq!(error["message"], "1~5文字で入力してください.");
+    }
+
+    #[test]
+    fn flatten_validation_errors_nested_and_list() {
+        let result = flatten(&Form {
+            code: "ok".to_string(),
+            item: Item { qty: 0 },
+            items: vec![Item { qty: 1 }, Item { qty: 11 }],
+        });
+        let mut keys = result.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["item.qty", "items[1].qty"]);
+        assert_eq!(result["items[1].qty"][0]["code"], "range");
+    }
+
+    #[test]
+    fn flatten_validation_errors_prefix() {
+        let mut errors = validator::ValidationErrors::new();
+        errors.add("csv", validator::ValidationError::new("no_rows"));
+        let mut result = serde_json::Map::new();
+        flatten_validation_errors("[3]", &errors, &mut result);
+        assert_eq!(result["[3].csv"][0]["code"], "no_rows");
+    }
+
+    #[test]
+    fn unprocessable_detail_is_translated() {
+        let err =
+            ApiCustomError::unprocessable("emp_deleted", [("empno", serde_json::json!(7369))]);
+        assert_eq!(err.detail(), "社員 [7369] は削除されています.");
+        assert_eq!(
+            i18n::LANG.sync_scope(i18n::Lang::En, || err.detail()),
+            "empno [7369] is deleted."
+        );
+    }
+
+    #[test]
+    fn row_error_keeps_status_and_prefixes_index() {
+        let err = ApiCustomError::RowError(
+            2,
+            Box::new(ApiCustomError::Conflict("uq_dept_dname_loc".to_string())),
+        );
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+        assert_eq!(err.code(), "conflict");
+        assert_eq!(
+            i18n::LANG.sync_scope(i18n::Lang::En, || err.detail()),
+            "[2] Conflict. constraint [uq_dept_dname_loc] is violated."
+        );
     }
 }
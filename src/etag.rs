@@ -0,0 +1,43 @@
+use actix_web::http::header::{EntityTag, Header, IfMatch, IF_MATCH};
+use actix_web::HttpRequest;
+use sea_orm::DbErr;
+
+use crate::error::ApiCustomError;
+
+/// ETag（レコードの version）
+pub fn etag(version: i32) -> EntityTag {
+    EntityTag::new_strong(version.to_string())
+}
+
+/// If-Match チェック
+///
+/// * 未指定 - `required` の場合は 428、それ以外はチェックしない
+/// * `*` - 常に一致
+/// * 一致するETagがない - 412
+pub fn verify_if_match(
+    req: &HttpRequest,
+    version: i32,
+    required: bool,
+) -> Result<(), ApiCustomError> {
+    if !req.headers().contains_key(IF_MATCH) {
+        return match required {
+            true => Err(ApiCustomError::PreconditionRequired),
+            false => Ok(()),
+        };
+    }
+
+    let current = etag(version);
+    match IfMatch::parse(req).map_err(actix_web::error::ErrorBadRequest)? {
+        IfMatch::Any => Ok(()),
+        IfMatch::Items(tags) if tags.iter().any(|tag| tag.strong_eq(&current)) => Ok(()),
+        IfMatch::Items(_) => Err(ApiCustomError::PreconditionFailed),
+    }
+}
+
+/// 更新競合（version 不一致で更新0件）の場合は 412
+pub fn stale_to_precondition_failed(err: DbErr) -> ApiCustomError {
+    match err {
+        DbErr::RecordNotUpdated => ApiCustomError::PreconditionFailed,
+        err => err.into(),
+    }
+}
@@ -0,0 +1,8 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+/// 構造体: 時点指定クエリ
+#[derive(serde::Deserialize, Debug)]
+pub struct AsOfQuery {
+    // 指定日時（RFC 3339）時点の状態を取得する（履歴テーブルから再現）
+    pub as_of: Option<DateTimeWithTimeZone>,
+}
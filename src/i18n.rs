@@ -0,0 +1,153 @@
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// 言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Lang {
+    #[default]
+    Ja,
+    En,
+}
+
+impl FromStr for Lang {
+    type Err = ();
+
+    /// 言語タグ（ja, en-US 等. 主言語のみ判定）
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        let primary = tag.split('-').next().unwrap_or_default().trim();
+        match primary.to_ascii_lowercase().as_str() {
+            "ja" => Ok(Lang::Ja),
+            "en" => Ok(Lang::En),
+            _ => Err(()),
+        }
+    }
+}
+
+/// メッセージカタログ（キー: `validation.{code}`・`validation.{code}.{field}`・`error.{code}`）
+static CATALOG: Lazy<HashMap<Lang, HashMap<String, String>>> = Lazy::new(|| {
+    let load = |json: &str| -> HashMap<String, String> {
+        serde_json::from_str(json).expect("message catalog should be valid json.")
+    };
+    HashMap::from([
+        (Lang::Ja, load(include_str!("../locales/ja.json"))),
+        (Lang::En, load(include_str!("../locales/en.json"))),
+    ])
+});
+
+tokio::task_local! {
+    /// 言語（リクエスト処理中のタスクで参照する）
+    pub static LANG: Lang;
+}
+
+/// 処理中のリクエストの言語
+///
+/// リクエスト処理外（LANG のスコープ外）の場合はデフォルト
+pub fn current_lang() -> Lang {
+    LANG.try_with(|lang| *lang).unwrap_or_default()
+}
+
+/// Accept-Language から言語を選択する
+///
+/// q 値の高い順に対応している言語を選択し、いずれも対応していない場合は default
+pub fn negotiate(accept_language: Option<&str>, default: Lang) -> Lang {
+    let mut tags: Vec<(&str, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim();
+            let q = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map(|q| q.parse().unwrap_or(0.0))
+                .unwrap_or(1.0);
+            Some((tag, q))
+        })
+        .filter(|(_, q)| *q > 0.0)
+        .collect();
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter()
+        .find_map(|(tag, _)| tag.parse().ok())
+        .unwrap_or(default)
+}
+
+/// メッセージ取得（`{パラメータ名}` をパラメータの値で置換する）
+///
+/// カタログに存在しない場合は None
+pub fn translate(
+    key: &str,
+    params: &HashMap<Cow<'static, str>, serde_json::Value>,
+) -> Option<String> {
+    let message = CATALOG.get(&current_lang())?.get(key)?;
+    Some(
+        params
+            .iter()
+            .fold(message.clone(), |message, (name, value)| {
+                let value = match value {
+                    serde_json::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                message.replace(&format!("{{{}}}", name), &value)
+            }),
+    )
+}
+
+/// バリデートエラーのメッセージ
+///
+/// `validation.{code}.{field}`、`validation.{code}` の順にカタログを検索し、いずれも存在しない場合はエラーのメッセージ（なければ code）
+pub fn validation_message(field: &str, err: &validator::ValidationError) -> String {
+    translate(&format!("validation.{}.{}", err.code, field), &err.params)
+        .or_else(|| translate(&format!("validation.{}", err.code), &err.params))
+        .or_else(|| err.message.as_ref().map(|message| message.to_string()))
+        .unwrap_or_else(|| err.code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate_prefers_highest_q() {
+        assert_eq!(negotiate(Some("ja;q=0.5, en-US;q=0.9"), Lang::Ja), Lang::En);
+        assert_eq!(negotiate(Some("en;q=0.3, ja"), Lang::En), Lang::Ja);
+    }
+
+    #[test]
+    fn negotiate_skips_unsupported_and_zero_q() {
+        assert_eq!(
+            negotiate(Some("fr, de;q=0.8, en;q=0.1"), Lang::Ja),
+            Lang::En
+        );
+        assert_eq!(negotiate(Some("en;q=0, fr"), Lang::Ja), Lang::Ja);
+    }
+
+    #[test]
+    fn negotiate_default() {
+        assert_eq!(negotiate(None, Lang::En), Lang::En);
+        assert_eq!(negotiate(Some(""), Lang::Ja), Lang::Ja);
+        assert_eq!(negotiate(Some("*"), Lang::En), Lang::En);
+    }
+
+    #[test]
+    fn translate_replaces_params() {
+        let params = HashMap::from([
+            (Cow::Borrowed("min"), serde_json::json!(1)),
+            (Cow::Borrowed("max"), serde_json::json!(10)),
+        ]);
+        assert_eq!(
+            translate("validation.range", &params).as_deref(),
+            Some("1~10の値で入力してください.")
+        );
+        assert_eq!(
+            LANG.sync_scope(Lang::En, || translate("validation.range", &params))
+                .as_deref(),
+            Some("Must be between 1 and 10.")
+        );
+    }
+
+    #[test]
+    fn translate_unknown_key() {
+        assert_eq!(translate("validation.unknown", &HashMap::new()), None);
+    }
+}
@@ -10,12 +10,26 @@ mod entities;
 mod error;
 use crate::error::ApiCustomError;
 mod services;
+use crate::services::audit_service::*;
 use crate::services::dept_service::*;
 use crate::services::emp_service::*;
+use crate::services::metrics_service::*;
+use crate::services::org_service::*;
+use crate::services::report_service::*;
+use crate::services::salgrade_service::*;
+use crate::services::search_service::*;
 mod state;
 use crate::state::AppState;
 mod middleware;
-use crate::middleware::access_log;
+use crate::middleware::{access_log, locale, request_id};
+mod context;
+mod csv_io;
+mod etag;
+mod history;
+mod i18n;
+mod merge_patch;
+mod metrics;
+mod pagination;
 
 /// hello
 ///
@@ -134,13 +148,10 @@ async fn route_unmatch() -> Result<HttpResponse, ApiCustomError> {
 /// 構造体: バリデーション（get）
 #[derive(serde::Deserialize, serde::Serialize, validator::Validate)]
 struct ValidateGetStruct {
-    #[validate(
-        range(min = 1, max = 10, message = "1~10の値で入力してください."),
-        required(message = "必須.")
-    )]
+    #[validate(range(min = 1, max = 10), required)]
     x: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[validate(length(min = 2, max = 5, message = "2~5文字で入力してください."))]
+    #[validate(length(min = 2, max = 5))]
     y: Option<String>,
 }
 
@@ -188,25 +199,19 @@ static REGEX_POST_CODE: once_cell::sync::Lazy<regex::Regex> =
 /// 構造体: バリデーション（post）
 #[derive(serde::Deserialize, serde::Serialize, validator::Validate)]
 struct ValidatePostStruct {
-    #[validate(
-        length(min = 1, max = 10, message = "名前を入力してください.[1~10文字]"),
-        required(message = "名前は必須項目です.")
-    )]
+    #[validate(length(min = 1, max = 10), required)]
     name: Option<String>,
 
-    #[validate(
-        range(min = 1, max = 12, message = "誕生月を入力してください.[1~12]"),
-        required(message = "誕生月は必須項目です.")
-    )]
+    #[validate(range(min = 1, max = 12), required)]
     birth_month: Option<u32>,
 
-    #[validate(email(message = "メールアドレスの形式が正しくありません."))]
+    #[validate(email)]
     email: Option<String>,
 
-    #[validate(url(message = "URLの形式が正しくありません."))]
+    #[validate(url)]
     hp_url: Option<String>,
 
-    #[validate(regex(path = *REGEX_POST_CODE, message = "郵便番号形式が正しくありません."))]
+    #[validate(regex(path = *REGEX_POST_CODE))]
     post_code: Option<String>,
 }
 
@@ -259,7 +264,8 @@ async fn main() -> std::io::Result<()> {
             tracing::Level::from_str(&env::var("LOG_LEVEL").unwrap_or(String::from("info")))
                 .unwrap_or(tracing::Level::INFO),
         )
-        // .with_current_span(true)
+        // リクエストID（span）をログに付与する
+        .with_current_span(true)
         // .with_span_events(tracing_subscriber::fmt::format::FmtSpan::NONE)
         // .with_thread_ids(true)
         // .with_thread_names(true)
@@ -268,6 +274,9 @@ async fn main() -> std::io::Result<()> {
         // .with_line_number(true)
         .init();
 
+    // エラーレスポンス（debug の場合はサーバーエラーの詳細を含める）
+    error::set_debug_mode(env::var("APP_MODE").unwrap_or_default() == "debug");
+
     // db connection. see: https://www.sea-ql.org/SeaORM/docs/install-and-config/connection/
     let mut opt =
         ConnectOptions::new(env::var("DATABASE_URL").expect("DB Connection should be set."));
@@ -278,14 +287,27 @@ async fn main() -> std::io::Result<()> {
         .unwrap_or(tracing::log::LevelFilter::Debug),
     );
 
-    let conn = Database::connect(opt).await.unwrap();
-    let state = AppState { conn };
+    let mut conn = Database::connect(opt).await.unwrap();
+    conn.set_metric_callback(|info| metrics::METRICS.observe_query(info));
+    let state = AppState {
+        conn,
+        require_if_match: env::var("REQUIRE_IF_MATCH").unwrap_or_default() == "true",
+        trust_user_headers: env::var("TRUST_USER_HEADERS").unwrap_or_default() == "true",
+        default_lang: env::var("DEFAULT_LANG")
+            .unwrap_or_default()
+            .parse()
+            .unwrap_or_default(),
+    };
 
     // http
     HttpServer::new(move || {
         App::new()
+            .wrap(actix_web::middleware::from_fn(locale))
             .wrap(actix_web::middleware::from_fn(access_log))
+            .wrap(actix_web::middleware::from_fn(request_id))
             .app_data(web::Data::new(state.clone()))
+            // リクエストボディ（Bytes・String. CSV取込）の上限. Json は JsonConfig のため対象外
+            .app_data(web::PayloadConfig::new(csv_io::IMPORT_PAYLOAD_LIMIT))
             .service(hello)
             .service(echo)
             .service(get_path1)
@@ -297,13 +319,39 @@ async fn main() -> std::io::Result<()> {
             .service(post_dept)
             .service(get_dept_all)
             .service(get_dept_by_key)
+            .service(put_dept)
             .service(patch_dept)
             .service(delete_dept)
+            .service(post_dept_restore)
             .service(get_emp_all)
             .service(get_emp_by_key)
             .service(post_emp)
+            .service(put_emp)
             .service(patch_emp)
             .service(delete_emp)
+            .service(post_emp_restore)
+            .service(post_emp_transfer)
+            .service(get_dept_emp_all)
+            .service(post_dept_emp)
+            .service(post_emp_bulk)
+            .service(get_dept_csv)
+            .service(post_dept_import)
+            .service(get_emp_csv)
+            .service(post_emp_import)
+            .service(get_emp_reports)
+            .service(get_emp_chain)
+            .service(get_salgrade_all)
+            .service(get_salgrade_by_key)
+            .service(post_salgrade)
+            .service(patch_salgrade)
+            .service(delete_salgrade)
+            .service(get_dept_summary_all)
+            .service(get_dept_summary_by_key)
+            .service(get_audit_all)
+            .service(get_emp_history)
+            .service(get_search)
+            .service(get_search_autocomplete)
+            .service(get_metrics)
             .route("/hey", web::get().to(manual_hello))
             .default_service(web::route().to(route_unmatch))
     })
@@ -0,0 +1,158 @@
+use sea_orm::*;
+use serde::{Deserialize, Deserializer};
+use std::str::FromStr;
+
+/// デシリアライズ: null 不可のフィールド
+///
+/// `#[serde(default, deserialize_with = "non_null")]` で使用する（未指定: None / null: エラー）
+pub fn non_null<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// デシリアライズ: null 可のフィールド
+///
+/// `#[serde(default, deserialize_with = "nullable")]` で使用する（未指定: None / null: Some(None)）
+pub fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// JSON Merge Patch（RFC 7396）の適用
+///
+/// 現在のレコードにパッチをマージし、パッチに含まれるフィールドのみ Set とした ActiveModel を返却する.
+/// null のフィールドは null（NULL）に更新する.
+pub fn merge_patch<M, A>(
+    model: M,
+    patch: &serde_json::Map<String, serde_json::Value>,
+) -> Result<A, DbErr>
+where
+    M: ModelTrait + IntoActiveModel<A> + serde::Serialize + for<'de> Deserialize<'de>,
+    A: ActiveModelTrait<Entity = M::Entity>,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    for<'de> <A::Entity as EntityTrait>::Model: Deserialize<'de>,
+{
+    // 現在値 + パッチ
+    let mut merged = serde_json::to_value(&model).map_err(|err| DbErr::Json(err.to_string()))?;
+    for (key, value) in patch {
+        merged[key] = value.clone();
+    }
+    let patched = A::from_json(merged)?;
+
+    // パッチに含まれるフィールドのみ反映
+    let mut active_model = model.into_active_model();
+    for key in patch.keys() {
+        let column = <A::Entity as EntityTrait>::Column::from_str(key)
+            .map_err(|_| DbErr::Json(format!("unknown field [{}]", key)))?;
+        if let Some(value) = patched.get(column).into_value() {
+            active_model.set(column, value);
+        }
+    }
+    Ok(active_model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::emp;
+    use chrono::NaiveDate;
+    use sea_orm::prelude::Decimal;
+    use serde_json::json;
+
+    #[derive(serde::Deserialize, serde::Serialize, Debug)]
+    struct PatchJson {
+        #[serde(
+            default,
+            deserialize_with = "non_null",
+            skip_serializing_if = "Option::is_none"
+        )]
+        ename: Option<String>,
+        #[serde(
+            default,
+            deserialize_with = "nullable",
+            skip_serializing_if = "Option::is_none"
+        )]
+        mgr: Option<Option<i32>>,
+        #[serde(
+            default,
+            deserialize_with = "nullable",
+            skip_serializing_if = "Option::is_none"
+        )]
+        comm: Option<Option<Decimal>>,
+    }
+
+    fn emp() -> emp::Model {
+        emp::Model {
+            empno: 7499,
+            ename: "ALLEN".to_string(),
+            job: "SALESMAN".to_string(),
+            mgr: Some(7698),
+            hiredate: NaiveDate::from_ymd_opt(1981, 2, 20).unwrap(),
+            sal: Decimal::new(160000, 2),
+            comm: Some(Decimal::new(30000, 2)),
+            deptno: 30,
+            version: 1,
+            deleted_at: None,
+        }
+    }
+
+    fn patch(body: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        let form: PatchJson = serde_json::from_value(body).unwrap();
+        json!(form).as_object().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn absent_fields_are_none() {
+        let form: PatchJson = serde_json::from_value(json!({})).unwrap();
+        assert!(form.ename.is_none());
+        assert!(form.mgr.is_none());
+        assert!(form.comm.is_none());
+    }
+
+    #[test]
+    fn nullable_distinguishes_null_from_value() {
+        let form: PatchJson = serde_json::from_value(json!({"mgr": null, "comm": 0})).unwrap();
+        assert_eq!(form.mgr, Some(None));
+        assert_eq!(form.comm, Some(Some(Decimal::ZERO)));
+    }
+
+    #[test]
+    fn non_null_rejects_null() {
+        assert!(serde_json::from_value::<PatchJson>(json!({"ename": null})).is_err());
+    }
+
+    #[test]
+    fn merge_patch_sets_only_patched_columns() {
+        let emp = emp();
+        let active_model: emp::ActiveModel =
+            merge_patch(emp.clone(), &patch(json!({"ename": "WARD"}))).unwrap();
+        assert_eq!(active_model.ename, Set("WARD".to_string()));
+        assert!(active_model.job.is_unchanged());
+        assert!(active_model.mgr.is_unchanged());
+        assert!(active_model.sal.is_unchanged());
+        assert!(active_model.comm.is_unchanged());
+        assert_eq!(active_model.mgr, Unchanged(emp.mgr));
+    }
+
+    #[test]
+    fn merge_patch_null_clears_nullable_columns() {
+        let active_model: emp::ActiveModel =
+            merge_patch(emp(), &patch(json!({"mgr": null, "comm": null}))).unwrap();
+        assert_eq!(active_model.mgr, Set(None));
+        assert_eq!(active_model.comm, Set(None));
+        assert!(active_model.ename.is_unchanged());
+    }
+
+    #[test]
+    fn merge_patch_rejects_unknown_field() {
+        let mut patch = serde_json::Map::new();
+        patch.insert("unknown".to_string(), json!(1));
+        assert!(merge_patch::<emp::Model, emp::ActiveModel>(emp(), &patch).is_err());
+    }
+}
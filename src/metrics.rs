@@ -0,0 +1,129 @@
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use sea_orm::DatabaseConnection;
+
+/// 構造体: メトリクス（Prometheus）
+pub struct Metrics {
+    registry: Registry,
+    // HTTPリクエスト数（method, route, status）
+    http_requests_total: IntCounterVec,
+    // HTTPリクエスト処理時間（method, route, status）
+    http_request_duration_seconds: HistogramVec,
+    // DBコネクションプール（state: size, idle, in_use）
+    db_pool_connections: IntGaugeVec,
+    // DBクエリ実行時間（query_type: select, insert, update, delete, other）
+    db_query_duration_seconds: HistogramVec,
+}
+
+/// メトリクス
+pub static METRICS: Lazy<Metrics> = Lazy::new(|| {
+    let registry = Registry::new();
+    let http_requests_total = IntCounterVec::new(
+        Opts::new("http_requests_total", "Total number of HTTP requests."),
+        &["method", "route", "status"],
+    )
+    .expect("metric should be valid.");
+    let http_request_duration_seconds = HistogramVec::new(
+        HistogramOpts::new(
+            "http_request_duration_seconds",
+            "HTTP request latency in seconds.",
+        ),
+        &["method", "route", "status"],
+    )
+    .expect("metric should be valid.");
+    let db_pool_connections = IntGaugeVec::new(
+        Opts::new("db_pool_connections", "Number of DB pool connections."),
+        &["state"],
+    )
+    .expect("metric should be valid.");
+    let db_query_duration_seconds = HistogramVec::new(
+        HistogramOpts::new("db_query_duration_seconds", "DB query latency in seconds."),
+        &["query_type"],
+    )
+    .expect("metric should be valid.");
+
+    registry
+        .register(Box::new(http_requests_total.clone()))
+        .expect("metric should be registered once.");
+    registry
+        .register(Box::new(http_request_duration_seconds.clone()))
+        .expect("metric should be registered once.");
+    registry
+        .register(Box::new(db_pool_connections.clone()))
+        .expect("metric should be registered once.");
+    registry
+        .register(Box::new(db_query_duration_seconds.clone()))
+        .expect("metric should be registered once.");
+
+    Metrics {
+        registry,
+        http_requests_total,
+        http_request_duration_seconds,
+        db_pool_connections,
+        db_query_duration_seconds,
+    }
+});
+
+/// ルート未定義の場合の route ラベル（URIをラベルにしない）
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+impl Metrics {
+    /// HTTPリクエストの記録
+    ///
+    /// * `route` - ルートのパターン（/emp/{empno} 等. 未定義の場合は None）
+    pub fn observe_http(&self, method: &str, route: Option<&str>, status: u16, seconds: f64) {
+        let status = status.to_string();
+        let labels = [method, route.unwrap_or(UNMATCHED_ROUTE), status.as_str()];
+        self.http_requests_total.with_label_values(&labels).inc();
+        self.http_request_duration_seconds
+            .with_label_values(&labels)
+            .observe(seconds);
+    }
+
+    /// DBクエリの記録（SeaORM のメトリクスコールバックから呼び出す）
+    pub fn observe_query(&self, info: &sea_orm::metric::Info<'_>) {
+        self.db_query_duration_seconds
+            .with_label_values(&[query_type(&info.statement.sql)])
+            .observe(info.elapsed.as_secs_f64());
+    }
+
+    /// テキスト形式（Prometheus exposition format）で出力する
+    ///
+    /// DBコネクションプールの状態は出力時点の値を設定する
+    pub fn render(&self, conn: &DatabaseConnection) -> Result<String, anyhow::Error> {
+        let pool = conn.get_postgres_connection_pool();
+        let size = i64::from(pool.size());
+        let idle = pool.num_idle() as i64;
+        self.db_pool_connections
+            .with_label_values(&["size"])
+            .set(size);
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle);
+        self.db_pool_connections
+            .with_label_values(&["in_use"])
+            .set(size - idle);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+/// SQL の種類（先頭のキーワード. WITH 句は other）
+fn query_type(sql: &str) -> &'static str {
+    let keyword = sql
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match keyword.as_str() {
+        "select" => "select",
+        "insert" => "insert",
+        "update" => "update",
+        "delete" => "delete",
+        _ => "other",
+    }
+}
@@ -1,10 +1,56 @@
 use actix_web::{
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
+    http::header::{self, HeaderName, HeaderValue},
     middleware::Next,
-    Error,
+    web, Error, HttpMessage,
 };
 use std::time::Instant;
+use tracing::Instrument;
+
+use crate::context::{RequestId, REQUEST_ID, REQUEST_ID_HEADER, REQUEST_ID_MAX_LEN};
+use crate::i18n::{negotiate, LANG};
+use crate::metrics::METRICS;
+use crate::state::AppState;
+
+/// ミドルウェア: リクエストID
+///
+/// X-Request-Id を引き継ぐ（未指定・不正な場合・REQUEST_ID_MAX_LEN を超える場合は UUIDv7 を採番する）.
+/// リクエストIDを持つ span 内で処理し（ログに付与される）、リクエストの extensions・タスクローカル・レスポンスヘッダに設定する
+pub async fn request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    // pre processing
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| {
+            !value.is_empty()
+                && value.len() <= REQUEST_ID_MAX_LEN
+                && value.chars().all(|c| c.is_ascii_graphic())
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::now_v7().to_string());
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+    let span = tracing::info_span!("request", request_id = %request_id);
+
+    // invoke（エラーレスポンスで参照するため、リクエストIDをタスクローカルに設定する）
+    let mut res = REQUEST_ID
+        .scope(Some(request_id.clone()), next.call(req))
+        .instrument(span)
+        .await?;
+
+    // post processing
+    if let (Ok(name), Ok(value)) = (
+        HeaderName::try_from(REQUEST_ID_HEADER),
+        HeaderValue::from_str(&request_id),
+    ) {
+        res.headers_mut().insert(name, value);
+    }
+    Ok(res)
+}
 
 /// ミドルウェア: アクセスログ
 pub async fn access_log(
@@ -20,8 +66,35 @@ pub async fn access_log(
     let res = next.call(req).await?;
 
     // post processing
+    let elapsed = start_time.elapsed();
     let status = res.status().as_u16();
-    let exec_time = format!("{}ms", start_time.elapsed().as_millis());
+    let exec_time = format!("{}ms", elapsed.as_millis());
     tracing::info!(status, method, uri, exec_time);
+    METRICS.observe_http(
+        &method,
+        res.request().match_pattern().as_deref(),
+        status,
+        elapsed.as_secs_f64(),
+    );
     Ok(res)
 }
+
+/// ミドルウェア: 言語選択
+///
+/// Accept-Language からメッセージの言語を選択する（対応していない場合は DEFAULT_LANG）
+pub async fn locale(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let default_lang = req
+        .app_data::<web::Data<AppState>>()
+        .map(|state| state.default_lang)
+        .unwrap_or_default();
+    let lang = negotiate(
+        req.headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok()),
+        default_lang,
+    );
+    LANG.scope(lang, next.call(req)).await
+}
@@ -0,0 +1,267 @@
+use actix_web::{http::header, HttpRequest, HttpResponse};
+use sea_orm::sea_query::ValueType;
+use sea_orm::*;
+
+/// 取得件数（デフォルト）
+const DEFAULT_LIMIT: u64 = 100;
+
+/// ページングで使用するクエリストリングのキー
+const PAGE_KEYS: [&str; 3] = ["limit", "offset", "after"];
+
+/// 構造体: ページングクエリ
+///
+/// * `limit` + `offset` - オフセットページング
+/// * `limit` + `after` - キーセットページング（キーが`after`より大きいレコード）
+#[derive(serde::Deserialize, validator::Validate, Debug)]
+#[validate(schema(function = "validate_page_query"))]
+pub struct PageQuery {
+    // limit
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<u64>,
+
+    // offset（DBへは i64 で渡すため上限を設ける）
+    #[validate(range(max = 1000000))]
+    pub offset: Option<u64>,
+
+    // after
+    pub after: Option<i32>,
+}
+
+impl PageQuery {
+    /// 取得件数
+    pub fn limit(&self) -> u64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT)
+    }
+}
+
+/// offset と after の同時指定は不可
+fn validate_page_query(query: &PageQuery) -> Result<(), validator::ValidationError> {
+    if query.offset.is_some() && query.after.is_some() {
+        return Err(validator::ValidationError::new("offset_with_after"));
+    }
+    Ok(())
+}
+
+/// 構造体: ページ
+pub struct Page<M> {
+    pub items: Vec<M>,
+    pub total: u64,
+    limit: u64,
+    // オフセットページングの場合のみ Some
+    offset: Option<u64>,
+    // キーセットページングの場合の次ページのキー
+    next_after: Option<i32>,
+}
+
+impl<M> Page<M> {
+    /// 要素の変換
+    pub fn map<N>(self, f: impl FnMut(M) -> N) -> Page<N> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            limit: self.limit,
+            offset: self.offset,
+            next_after: self.next_after,
+        }
+    }
+}
+
+/// ページ取得
+///
+/// 総件数は SeaORM の Paginator、キーセットページングは Cursor で取得する.
+/// オフセットページングでは`key`を最後のソート順として付与する（ページ間の順序を安定させるため）.
+pub async fn fetch_page<E, C>(
+    conn: &C,
+    select: Select<E>,
+    key: E::Column,
+    query: &PageQuery,
+) -> Result<Page<E::Model>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    C: ConnectionTrait,
+{
+    let total = select.clone().count(conn).await?;
+    let limit = query.limit();
+
+    match query.after {
+        Some(after) => {
+            let mut cursor = select.cursor_by(key);
+            let items = cursor.after(after).first(limit).all(conn).await?;
+            let next_after = if items.len() as u64 == limit {
+                items
+                    .last()
+                    .and_then(|item| <i32 as ValueType>::try_from(item.get(key)).ok())
+            } else {
+                None
+            };
+            Ok(Page {
+                items,
+                total,
+                limit,
+                offset: None,
+                next_after,
+            })
+        }
+        None => {
+            let offset = query.offset.unwrap_or(0);
+            let items = select
+                .order_by_asc(key)
+                .offset(offset)
+                .limit(limit)
+                .all(conn)
+                .await?;
+            Ok(Page {
+                items,
+                total,
+                limit,
+                offset: Some(offset),
+                next_after: None,
+            })
+        }
+    }
+}
+
+/// ページのレスポンス
+///
+/// ボディは配列（0件の場合は`[]`）. 総件数は`X-Total-Count`、前後ページは`Link`（RFC 8288）で返却する.
+pub fn page_response<M: serde::Serialize>(req: &HttpRequest, page: &Page<M>) -> HttpResponse {
+    let mut builder = HttpResponse::Ok();
+    builder.insert_header(("X-Total-Count", page.total.to_string()));
+
+    let links = page_links(req, page);
+    if !links.is_empty() {
+        builder.insert_header((header::LINK, links.join(", ")));
+    }
+    builder.json(&page.items)
+}
+
+/// Link ヘッダの値（next/prev/first/last）
+fn page_links<M>(req: &HttpRequest, page: &Page<M>) -> Vec<String> {
+    // ページング以外のクエリストリングは引き継ぐ
+    let params: Vec<&str> = req
+        .query_string()
+        .split('&')
+        .filter(|param| {
+            let key = param.split('=').next().unwrap_or_default();
+            !param.is_empty() && !PAGE_KEYS.contains(&key)
+        })
+        .collect();
+    let link = |page_params: String, rel: &str| {
+        let mut query = params.clone();
+        query.push(&page_params);
+        format!("<{}?{}>; rel=\"{}\"", req.path(), query.join("&"), rel)
+    };
+
+    let limit = page.limit;
+    let mut links = Vec::new();
+    match page.offset {
+        Some(offset) => {
+            let last = page.total.saturating_sub(1) / limit * limit;
+            let next = offset.saturating_add(limit);
+            if next < page.total {
+                links.push(link(format!("limit={}&offset={}", limit, next), "next"));
+            }
+            if offset > 0 {
+                links.push(link(
+                    format!("limit={}&offset={}", limit, offset.saturating_sub(limit)),
+                    "prev",
+                ));
+            }
+            links.push(link(format!("limit={}&offset=0", limit), "first"));
+            links.push(link(format!("limit={}&offset={}", limit, last), "last"));
+        }
+        None => {
+            if let Some(next_after) = page.next_after {
+                links.push(link(
+                    format!("limit={}&after={}", limit, next_after),
+                    "next",
+                ));
+            }
+            links.push(link(format!("limit={}", limit), "first"));
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn page(total: u64, limit: u64, offset: Option<u64>, next_after: Option<i32>) -> Page<()> {
+        Page {
+            items: Vec::new(),
+            total,
+            limit,
+            offset,
+            next_after,
+        }
+    }
+
+    #[test]
+    fn page_links_offset_middle_page() {
+        let req = TestRequest::with_uri("/emp?job=CLERK&limit=10&offset=10").to_http_request();
+        let links = page_links(&req, &page(35, 10, Some(10), None));
+        assert_eq!(
+            links,
+            vec![
+                "</emp?job=CLERK&limit=10&offset=20>; rel=\"next\"",
+                "</emp?job=CLERK&limit=10&offset=0>; rel=\"prev\"",
+                "</emp?job=CLERK&limit=10&offset=0>; rel=\"first\"",
+                "</emp?job=CLERK&limit=10&offset=30>; rel=\"last\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn page_links_offset_last_page_has_no_next() {
+        let req = TestRequest::with_uri("/emp?offset=30").to_http_request();
+        let links = page_links(&req, &page(35, 10, Some(30), None));
+        assert!(links.iter().all(|link| !link.contains("rel=\"next\"")));
+        assert!(links.contains(&"</emp?limit=10&offset=20>; rel=\"prev\"".to_string()));
+    }
+
+    #[test]
+    fn page_links_offset_does_not_overflow() {
+        let req = TestRequest::with_uri("/emp").to_http_request();
+        let links = page_links(&req, &page(5, 1000, Some(u64::MAX - 1), None));
+        assert!(links.iter().all(|link| !link.contains("rel=\"next\"")));
+    }
+
+    #[test]
+    fn page_links_empty_total() {
+        let req = TestRequest::with_uri("/dept").to_http_request();
+        let links = page_links(&req, &page(0, 100, Some(0), None));
+        assert_eq!(
+            links,
+            vec![
+                "</dept?limit=100&offset=0>; rel=\"first\"",
+                "</dept?limit=100&offset=0>; rel=\"last\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn page_links_keyset() {
+        let req = TestRequest::with_uri("/emp?after=7499&limit=2").to_http_request();
+        let links = page_links(&req, &page(14, 2, None, Some(7566)));
+        assert_eq!(
+            links,
+            vec![
+                "</emp?limit=2&after=7566>; rel=\"next\"",
+                "</emp?limit=2>; rel=\"first\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn page_query_rejects_offset_over_limit() {
+        let query = PageQuery {
+            limit: None,
+            offset: Some(9223372036854775808),
+            after: None,
+        };
+        assert!(validator::Validate::validate(&query).is_err());
+    }
+}
@@ -1,2 +1,8 @@
+pub mod audit_service;
 pub mod dept_service;
 pub mod emp_service;
+pub mod metrics_service;
+pub mod org_service;
+pub mod report_service;
+pub mod salgrade_service;
+pub mod search_service;
@@ -0,0 +1,188 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::*;
+use validator::Validate;
+
+use crate::context::RequestContext;
+use crate::entities::audit_log;
+use crate::entities::prelude::{AuditLog, Emp};
+use crate::error::ApiCustomError;
+use crate::pagination::{fetch_page, page_response, PageQuery};
+use crate::state::AppState;
+
+/// 監査対象: dept
+pub(crate) const ENTITY_DEPT: &str = "dept";
+
+/// 監査対象: emp
+pub(crate) const ENTITY_EMP: &str = "emp";
+
+/// 監査ログの操作
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AuditOperation {
+    Insert,
+    Update,
+    Delete,
+    Restore,
+    Transfer,
+}
+
+impl AuditOperation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Insert => "insert",
+            AuditOperation::Update => "update",
+            AuditOperation::Delete => "delete",
+            AuditOperation::Restore => "restore",
+            AuditOperation::Transfer => "transfer",
+        }
+    }
+}
+
+/// 構造体: 監査ログ検索クエリ
+#[derive(validator::Validate, serde::Deserialize, Debug)]
+#[validate(schema(function = "validate_audit_query"))]
+struct AuditQuery {
+    // 対象（dept / emp）
+    #[validate(custom(function = "validate_entity"))]
+    entity: Option<String>,
+
+    // 日時（以降. RFC 3339）
+    from: Option<DateTimeWithTimeZone>,
+
+    // 日時（以前. RFC 3339）
+    to: Option<DateTimeWithTimeZone>,
+}
+
+/// entity は dept / emp のいずれか
+fn validate_entity(entity: &str) -> Result<(), validator::ValidationError> {
+    match entity {
+        ENTITY_DEPT | ENTITY_EMP => Ok(()),
+        _ => Err(validator::ValidationError::new("entity")),
+    }
+}
+
+/// from <= to であること
+fn validate_audit_query(query: &AuditQuery) -> Result<(), validator::ValidationError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return Err(validator::ValidationError::new("audit_range"));
+        }
+    }
+    Ok(())
+}
+
+/// 監査ログ 一覧取得（ページング）
+#[get("/audit")]
+async fn get_audit_all(
+    req: HttpRequest,
+    query: Result<actix_web::web::Query<AuditQuery>, actix_web::Error>,
+    page: Result<actix_web::web::Query<PageQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋バリデート）
+    let query = query?.into_inner();
+    query.validate()?;
+    let page = page?.into_inner();
+    page.validate()?;
+
+    // 検索条件
+    let condition = Condition::all()
+        .add_option(
+            query
+                .entity
+                .map(|entity| audit_log::Column::Entity.eq(entity)),
+        )
+        .add_option(
+            query
+                .from
+                .map(|from| audit_log::Column::CreatedAt.gte(from)),
+        )
+        .add_option(query.to.map(|to| audit_log::Column::CreatedAt.lte(to)));
+
+    // レコード取得（ページング）
+    let logs = fetch_page(
+        &data.conn,
+        AuditLog::find().filter(condition),
+        audit_log::Column::Id,
+        &page,
+    )
+    .await?;
+
+    // レスポンス
+    Ok(page_response(&req, &logs))
+}
+
+/// emp 変更履歴取得（ページング）
+///
+/// 論理削除された社員も対象とする
+#[get("/emp/{empno}/history")]
+async fn get_emp_history(
+    req: HttpRequest,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    page: Result<actix_web::web::Query<PageQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let empno: i32 = path?.into_inner().try_into().unwrap();
+    let page = page?.into_inner();
+    page.validate()?;
+
+    // 存在チェック
+    if Emp::find_by_id(empno).one(&data.conn).await?.is_none() {
+        return Err(ApiCustomError::NotFound);
+    }
+
+    // レコード取得（ページング）
+    let logs = fetch_page(
+        &data.conn,
+        AuditLog::find()
+            .filter(audit_log::Column::Entity.eq(ENTITY_EMP))
+            .filter(audit_log::Column::EntityKey.eq(empno)),
+        audit_log::Column::Id,
+        &page,
+    )
+    .await?;
+
+    // レスポンス
+    Ok(page_response(&req, &logs))
+}
+
+/// 監査ログ記録（DB）
+///
+/// 更新処理と同一のトランザクションで呼び出すこと.
+/// * `before` - 更新前のレコード（登録の場合は None）
+/// * `after` - 更新後のレコード
+pub(crate) async fn record<C, M>(
+    conn: &C,
+    ctx: &RequestContext,
+    entity: &str,
+    entity_key: i32,
+    operation: AuditOperation,
+    before: Option<&M>,
+    after: Option<&M>,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    M: serde::Serialize,
+{
+    let to_json = |model: Option<&M>| {
+        model
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|err| DbErr::Json(err.to_string()))
+    };
+
+    audit_log::ActiveModel {
+        entity: Set(entity.to_string()),
+        entity_key: Set(entity_key),
+        operation: Set(operation.as_str().to_string()),
+        before: Set(to_json(before)?),
+        after: Set(to_json(after)?),
+        request_id: Set(ctx.request_id.clone()),
+        user_id: Set(ctx.user_id.clone()),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
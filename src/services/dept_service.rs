@@ -1,63 +1,180 @@
-use actix_web::{delete, get, patch, post, web, HttpResponse};
+use actix_web::{delete, get, http::header, patch, post, put, web, HttpRequest, HttpResponse};
+use chrono::Utc;
 use sea_orm::*;
 use serde_json::json;
 use validator::Validate;
 
+use crate::context::{DeletedQuery, RequestContext};
+use crate::csv_io::{csv_response, read_csv, ImportQuery, ImportResultJson};
+use crate::entities::ext::{ActiveFilter, AsOfFilter};
 use crate::entities::prelude::{Dept, Emp};
 use crate::entities::{dept, emp};
 use crate::error::ApiCustomError;
+use crate::etag::{etag, stale_to_precondition_failed, verify_if_match};
+use crate::history::AsOfQuery;
+use crate::merge_patch::{merge_patch, non_null};
+use crate::pagination::{fetch_page, page_response, PageQuery};
+use crate::services::audit_service::{self, AuditOperation, ENTITY_DEPT};
+use crate::services::emp_service::save_emp;
 use crate::state::AppState;
 
 /// 構造体: dept リクエストJson
 #[derive(validator::Validate, serde::Deserialize, serde::Serialize, Debug)]
 struct DeptRequestJson {
     // dname
-    #[validate(length(min = 1, max = 14, message = "1~14文字で入力してください."))]
+    #[validate(length(min = 1, max = 14))]
     pub dname: String,
 
     // loc
-    #[validate(length(min = 1, max = 13, message = "1~13文字で入力してください."))]
+    #[validate(length(min = 1, max = 13))]
     pub loc: String,
 }
 
-/// dept 全件取得
+/// 構造体: dept 部分更新リクエストJson（JSON Merge Patch）
+#[derive(validator::Validate, serde::Deserialize, serde::Serialize, Debug)]
+struct DeptPatchJson {
+    // dname
+    #[serde(
+        default,
+        deserialize_with = "non_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(length(min = 1, max = 14))]
+    pub dname: Option<String>,
+
+    // loc
+    #[serde(
+        default,
+        deserialize_with = "non_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(length(min = 1, max = 13))]
+    pub loc: Option<String>,
+}
+
+/// 構造体: dept 削除クエリ
+#[derive(validator::Validate, serde::Deserialize, Debug)]
+#[validate(schema(function = "validate_dept_delete_query"))]
+struct DeptDeleteQuery {
+    // 所属する社員の扱い（未指定の場合は社員が所属していると削除不可）
+    strategy: Option<DeleteStrategy>,
+
+    // 異動先の deptno（strategy=reassign の場合のみ）
+    to: Option<i32>,
+}
+
+/// dept 削除時の所属社員の扱い
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum DeleteStrategy {
+    // 異動先の部門に異動する
+    Reassign,
+    // 社員も削除する（管理者のみ）
+    Cascade,
+}
+
+/// to は strategy=reassign の場合に必須（それ以外は指定不可）
+fn validate_dept_delete_query(query: &DeptDeleteQuery) -> Result<(), validator::ValidationError> {
+    match (query.strategy, query.to) {
+        (Some(DeleteStrategy::Reassign), None) => {
+            Err(validator::ValidationError::new("to_required"))
+        }
+        (Some(DeleteStrategy::Reassign), Some(_)) | (_, None) => Ok(()),
+        (_, Some(_)) => Err(validator::ValidationError::new("to_not_allowed")),
+    }
+}
+
+/// 構造体: dept 削除結果Json
+#[derive(serde::Serialize, Debug)]
+struct DeptDeleteResultJson {
+    deptno: i32,
+    strategy: DeleteStrategy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<i32>,
+    // 異動した社員数
+    reassigned_emps: usize,
+    // 削除した社員数
+    deleted_emps: usize,
+}
+
+/// dept 一覧取得（ページング）
+///
+/// 論理削除されたレコードは除く（管理者は include_deleted=true で取得可）.
+/// as_of を指定した場合はその時点の状態を返却する
 #[get("/dept")]
-async fn get_dept_all(data: web::Data<AppState>) -> Result<HttpResponse, ApiCustomError> {
-    // レコード取得（全件）
-    let depts = Dept::find()
-        .order_by_asc(dept::Column::Deptno)
-        .all(&data.conn)
-        .await?;
+async fn get_dept_all(
+    req: HttpRequest,
+    ctx: RequestContext,
+    query: Result<actix_web::web::Query<PageQuery>, actix_web::Error>,
+    deleted_query: Result<actix_web::web::Query<DeletedQuery>, actix_web::Error>,
+    as_of_query: Result<actix_web::web::Query<AsOfQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋バリデート）
+    let query = query?.into_inner();
+    query.validate()?;
+    let include_deleted = deleted_query?.include_deleted(&ctx)?;
+    let as_of = as_of_query?.into_inner().as_of;
+
+    // レコード取得（ページング）
+    let select = match as_of {
+        Some(as_of) => Dept::find_as_of(as_of),
+        None => Dept::find(),
+    };
+    let select = match include_deleted {
+        true => select,
+        false => select.filter(dept::Column::DeletedAt.is_null()),
+    };
+    let depts = fetch_page(&data.conn, select, dept::Column::Deptno, &query).await?;
 
     // レスポンス
-    if depts.is_empty() {
-        return Ok(HttpResponse::NoContent().finish());
-    }
-    Ok(HttpResponse::Ok().json(depts))
+    Ok(page_response(&req, &depts))
 }
 
 /// dept キー取得
+///
+/// 論理削除されたレコードは除く（管理者は include_deleted=true で取得可）.
+/// as_of を指定した場合はその時点の状態を返却する（ETag なし）
 #[get("/dept/{deptno}")]
 async fn get_dept_by_key(
+    ctx: RequestContext,
     path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    deleted_query: Result<actix_web::web::Query<DeletedQuery>, actix_web::Error>,
+    as_of_query: Result<actix_web::web::Query<AsOfQuery>, actix_web::Error>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiCustomError> {
     // クエリストリング取得（＋型チェックと型変換）
     let deptno: i32 = path?.into_inner().try_into().unwrap();
+    let include_deleted = deleted_query?.include_deleted(&ctx)?;
+    let as_of = as_of_query?.into_inner().as_of;
 
     // キーに該当するレコードを取得する
-    let dept = Dept::find_by_id(deptno).one(&data.conn).await?;
+    let select = match as_of {
+        Some(as_of) => Dept::find_as_of(as_of).filter(dept::Column::Deptno.eq(deptno)),
+        None => Dept::find_by_id(deptno),
+    };
+    let select = match include_deleted {
+        true => select,
+        false => select.filter(dept::Column::DeletedAt.is_null()),
+    };
+    let dept = match select.one(&data.conn).await? {
+        Some(dept) => dept,
+        _ => return Err(ApiCustomError::NotFound),
+    };
 
     // レスポンス
-    match dept {
-        Some(dept) => Ok(HttpResponse::Ok().json(dept)),
-        _ => Err(ApiCustomError::NotFound),
+    match as_of {
+        Some(_) => Ok(HttpResponse::Ok().json(dept)),
+        None => Ok(HttpResponse::Ok()
+            .insert_header(header::ETag(etag(dept.version)))
+            .json(dept)),
     }
 }
 
 /// dept 登録
 #[post("/dept")]
 async fn post_dept(
+    ctx: RequestContext,
     form: Result<actix_web::web::Json<DeptRequestJson>, actix_web::Error>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiCustomError> {
@@ -65,18 +182,22 @@ async fn post_dept(
     let form = form?.into_inner();
     form.validate()?;
 
-    // DB登録
-    let dept = dept::ActiveModel::from_json(json!(form))?
-        .insert(&data.conn)
-        .await?;
+    // DB登録（トランザクション）
+    let txn = data.conn.begin().await?;
+    let dept = insert_dept(&txn, &ctx, &form).await?;
+    txn.commit().await?;
 
     // レスポンス
     Ok(HttpResponse::Created().json(dept))
 }
 
-/// dept 変更
-#[patch("/dept/{deptno}")]
-async fn patch_dept(
+/// dept 変更（全項目）
+///
+/// If-Match（ETag）が現在の version と一致しない場合は 412
+#[put("/dept/{deptno}")]
+async fn put_dept(
+    req: HttpRequest,
+    ctx: RequestContext,
     path: Result<actix_web::web::Path<u32>, actix_web::Error>,
     form: Result<actix_web::web::Json<DeptRequestJson>, actix_web::Error>,
     data: web::Data<AppState>,
@@ -88,55 +209,325 @@ async fn patch_dept(
     let form = form?;
     form.validate()?;
 
-    // 更新
-    let dept = Dept::find_by_id(deptno).one(&data.conn).await?;
+    // 更新（トランザクション）
+    let txn = data.conn.begin().await?;
+    let dept = Dept::find_active_by_id(deptno).one(&txn).await?;
     let updated_dept = match dept {
         Some(dept) => {
-            let mut dept_active_model = dept.into_active_model();
+            verify_if_match(&req, dept.version, data.require_if_match)?;
+            let mut dept_active_model = dept.clone().into_active_model();
             dept_active_model.set_from_json(json!(form))?;
-            dept_active_model.update(&data.conn).await?
+            update_dept(&txn, &ctx, &dept, dept_active_model, AuditOperation::Update).await?
         }
         _ => return Err(ApiCustomError::NotFound),
     };
+    txn.commit().await?;
 
     // レスポンス
-    Ok(HttpResponse::Ok().json(updated_dept))
+    Ok(HttpResponse::Ok()
+        .insert_header(header::ETag(etag(updated_dept.version)))
+        .json(updated_dept))
 }
 
-/// dept 削除
+/// dept 変更（部分更新. JSON Merge Patch）
+///
+/// 指定された項目のみバリデート・更新する.
+/// If-Match（ETag）が現在の version と一致しない場合は 412
+#[patch("/dept/{deptno}")]
+async fn patch_dept(
+    req: HttpRequest,
+    ctx: RequestContext,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    form: Result<actix_web::web::Json<DeptPatchJson>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（型チェック込）
+    let deptno: i32 = path?.into_inner().try_into().unwrap();
+
+    // バリデート
+    let form = form?;
+    form.validate()?;
+    let patch = json!(form).as_object().cloned().unwrap_or_default();
+
+    // 更新（トランザクション）
+    let txn = data.conn.begin().await?;
+    let dept = Dept::find_active_by_id(deptno).one(&txn).await?;
+    let updated_dept = match dept {
+        Some(dept) => {
+            verify_if_match(&req, dept.version, data.require_if_match)?;
+            let dept_active_model = merge_patch(dept.clone(), &patch)?;
+            update_dept(&txn, &ctx, &dept, dept_active_model, AuditOperation::Update).await?
+        }
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    txn.commit().await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok()
+        .insert_header(header::ETag(etag(updated_dept.version)))
+        .json(updated_dept))
+}
+
+/// dept 削除（論理削除）
+///
+/// 所属する社員がいる場合は strategy を指定する（1トランザクションで処理し、処理件数を返却する）.
+/// * 未指定 - 削除不可（参照制約 fk_emp_dept_active 違反）
+/// * `reassign` - 社員を to の部門に異動してから削除する
+/// * `cascade` - 社員も削除する（管理者のみ. 他の部門の社員の上司が含まれる場合は参照制約 fk_emp_mgr_active 違反）
+///
+/// If-Match（ETag）が現在の version と一致しない場合は 412
 #[delete("/dept/{deptno}")]
 async fn delete_dept(
+    req: HttpRequest,
+    ctx: RequestContext,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    query: Result<actix_web::web::Query<DeptDeleteQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（型チェック込）
+    let deptno: i32 = path?.into_inner().try_into().unwrap();
+    let query = query?.into_inner();
+    query.validate()?;
+
+    // 権限チェック
+    if query.strategy == Some(DeleteStrategy::Cascade) && !ctx.is_admin() {
+        return Err(ApiCustomError::Forbidden);
+    }
+
+    // トランザクション
+    let txn = data.conn.begin().await?;
+
+    // バージョンチェック
+    let dept = match Dept::find_active_by_id(deptno).one(&txn).await? {
+        Some(dept) => dept,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    verify_if_match(&req, dept.version, data.require_if_match)?;
+
+    // 所属する社員の異動・削除
+    // （参照先の部門・上司が削除済となる場合はコミット時に参照制約違反）
+    let emps = match query.strategy {
+        Some(_) => {
+            Emp::find_active()
+                .filter(emp::Column::Deptno.eq(deptno))
+                .order_by_asc(emp::Column::Empno)
+                .all(&txn)
+                .await?
+        }
+        None => Vec::new(),
+    };
+    let mut reassigned_emps = 0;
+    let mut deleted_emps = 0;
+    match (query.strategy, query.to) {
+        (None, _) => {}
+        (Some(DeleteStrategy::Reassign), Some(to)) => {
+            // 異動先チェック（コミットまで異動先の削除を防ぐため行ロック）
+            if to == deptno {
+                return Err(ApiCustomError::unprocessable(
+                    "dept_reassign_self",
+                    [("deptno", json!(to))],
+                ));
+            }
+            if Dept::find_active_by_id(to)
+                .lock_shared()
+                .one(&txn)
+                .await?
+                .is_none()
+            {
+                return Err(ApiCustomError::unprocessable(
+                    "dept_reassign_not_found",
+                    [("deptno", json!(to))],
+                ));
+            }
+            for emp in emps {
+                let mut emp_active_model = emp.clone().into_active_model();
+                emp_active_model.deptno = Set(to);
+                save_emp(&txn, &ctx, &emp, emp_active_model, AuditOperation::Update).await?;
+                reassigned_emps += 1;
+            }
+        }
+        (Some(DeleteStrategy::Cascade), _) => {
+            let deleted_at = Utc::now();
+            for emp in emps {
+                let mut emp_active_model = emp.clone().into_active_model();
+                emp_active_model.deleted_at = Set(Some(deleted_at.into()));
+                save_emp(&txn, &ctx, &emp, emp_active_model, AuditOperation::Delete).await?;
+                deleted_emps += 1;
+            }
+        }
+        (Some(DeleteStrategy::Reassign), None) => unreachable!("validated"),
+    }
+
+    // 削除日時を設定する
+    let mut dept_active_model = dept.clone().into_active_model();
+    dept_active_model.deleted_at = Set(Some(Utc::now().into()));
+    update_dept(&txn, &ctx, &dept, dept_active_model, AuditOperation::Delete).await?;
+    txn.commit().await?;
+
+    // レスポンス
+    match query.strategy {
+        Some(strategy) => Ok(HttpResponse::Ok().json(DeptDeleteResultJson {
+            deptno,
+            strategy,
+            to: query.to,
+            reassigned_emps,
+            deleted_emps,
+        })),
+        None => Ok(HttpResponse::NoContent().finish()),
+    }
+}
+
+/// dept 復元（論理削除の取消）
+///
+/// If-Match（ETag）が現在の version と一致しない場合は 412
+#[post("/dept/{deptno}/restore")]
+async fn post_dept_restore(
+    req: HttpRequest,
+    ctx: RequestContext,
     path: Result<actix_web::web::Path<u32>, actix_web::Error>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiCustomError> {
     // クエリストリング取得（型チェック込）
     let deptno: i32 = path?.into_inner().try_into().unwrap();
 
-    // 参照制約チェック
-    if exists_references(&data.conn, deptno).await? {
-        return Err(ApiCustomError::UnporcessibleEntity(format!(
-            "deptno [{}] can not delete.",
-            deptno
-        )));
+    // 削除チェック
+    let txn = data.conn.begin().await?;
+    let dept = match Dept::find_by_id(deptno).one(&txn).await? {
+        Some(dept) => dept,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    if dept.deleted_at.is_none() {
+        return Err(ApiCustomError::unprocessable(
+            "dept_not_deleted",
+            [("deptno", json!(deptno))],
+        ));
     }
+    verify_if_match(&req, dept.version, data.require_if_match)?;
+
+    // 削除日時をクリアする
+    let mut dept_active_model = dept.clone().into_active_model();
+    dept_active_model.deleted_at = Set(None);
+    let restored_dept = update_dept(
+        &txn,
+        &ctx,
+        &dept,
+        dept_active_model,
+        AuditOperation::Restore,
+    )
+    .await?;
+    txn.commit().await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok()
+        .insert_header(header::ETag(etag(restored_dept.version)))
+        .json(restored_dept))
+}
+
+/// dept CSVエクスポート
+#[get("/dept.csv")]
+async fn get_dept_csv(data: web::Data<AppState>) -> Result<HttpResponse, ApiCustomError> {
+    // レスポンス（全件. チャンクで取得・返却する）
+    csv_response(
+        data.conn.clone(),
+        Dept::find_active(),
+        dept::Column::Deptno,
+        "dept.csv",
+        &["deptno", "dname", "loc"],
+        |dept| vec![dept.deptno.to_string(), dept.dname, dept.loc],
+    )
+}
+
+/// dept CSVインポート
+///
+/// ヘッダ行: dname,loc. 全行を1トランザクションで登録する（dry_run=true の場合はロールバック）
+#[post("/dept/import")]
+async fn post_dept_import(
+    ctx: RequestContext,
+    query: Result<actix_web::web::Query<ImportQuery>, actix_web::Error>,
+    body: web::Bytes,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得
+    let dry_run = query?.into_inner().dry_run;
 
-    // キーに該当するレコードを削除する
-    let result = Dept::delete_by_id(deptno).exec(&data.conn).await?;
+    // CSV読込（＋バリデート）
+    let forms: Vec<DeptRequestJson> = read_csv(&body)?;
+
+    // DB登録（トランザクション）
+    let txn = data.conn.begin().await?;
+    for (index, form) in forms.iter().enumerate() {
+        insert_dept(&txn, &ctx, form)
+            .await
+            .map_err(|err| ApiCustomError::RowError(index, Box::new(err)))?;
+    }
+    match dry_run {
+        true => txn.rollback().await?,
+        false => txn.commit().await?,
+    }
 
     // レスポンス
-    if result.rows_affected == 0 {
-        return Err(ApiCustomError::NotFound);
+    let result = ImportResultJson {
+        rows: forms.len(),
+        dry_run,
+    };
+    match dry_run {
+        true => Ok(HttpResponse::Ok().json(result)),
+        false => Ok(HttpResponse::Created().json(result)),
     }
-    Ok(HttpResponse::NoContent().finish())
 }
 
-/// 参照制約チェック
+/// dept 登録（DB）
+///
+/// 監査ログを記録する
+async fn insert_dept<C: ConnectionTrait>(
+    conn: &C,
+    ctx: &RequestContext,
+    form: &DeptRequestJson,
+) -> Result<dept::Model, ApiCustomError> {
+    let dept = dept::ActiveModel::from_json(json!(form))?
+        .insert(conn)
+        .await?;
+    audit_service::record(
+        conn,
+        ctx,
+        ENTITY_DEPT,
+        dept.deptno,
+        AuditOperation::Insert,
+        None,
+        Some(&dept),
+    )
+    .await?;
+    Ok(dept)
+}
+
+/// dept 更新（DB）
+///
+/// * `dept` - 更新前のレコード
 ///
-/// 引数のdeptnoが設定されたempテーブルの有無を返却
-async fn exists_references(conn: &DatabaseConnection, deptno: i32) -> Result<bool, DbErr> {
-    Ok(Emp::find()
-        .filter(emp::Column::Deptno.eq(deptno))
-        .one(conn)
-        .await?
-        .is_some())
+/// version をインクリメントし、監査ログを記録する.
+/// 取得時から version が変わっている（他で更新された）場合は 412
+async fn update_dept<C: ConnectionTrait>(
+    conn: &C,
+    ctx: &RequestContext,
+    dept: &dept::Model,
+    mut dept_active_model: dept::ActiveModel,
+    operation: AuditOperation,
+) -> Result<dept::Model, ApiCustomError> {
+    dept_active_model.version = Set(dept.version + 1);
+    let updated_dept = Dept::update(dept_active_model)
+        .filter(dept::Column::Version.eq(dept.version))
+        .exec(conn)
+        .await
+        .map_err(stale_to_precondition_failed)?;
+    audit_service::record(
+        conn,
+        ctx,
+        ENTITY_DEPT,
+        dept.deptno,
+        operation,
+        Some(dept),
+        Some(&updated_dept),
+    )
+    .await?;
+    Ok(updated_dept)
 }
@@ -1,23 +1,36 @@
-use actix_web::{delete, get, patch, post, web, HttpResponse};
-use chrono::NaiveDate;
+use actix_web::{delete, get, http::header, patch, post, put, web, HttpRequest, HttpResponse};
+use chrono::{NaiveDate, Utc};
+use sea_orm::prelude::Decimal;
 use sea_orm::*;
 use serde_json::json;
+use std::collections::HashMap;
+use std::str::FromStr;
 use validator::Validate;
 
+use crate::context::{DeletedQuery, RequestContext};
+use crate::csv_io::{csv_response, read_csv, ImportQuery, ImportResultJson};
 use crate::entities::emp;
+use crate::entities::ext::{ActiveFilter, AsOfFilter};
 use crate::entities::prelude::{Dept, Emp};
 use crate::error::ApiCustomError;
+use crate::etag::{etag, stale_to_precondition_failed, verify_if_match};
+use crate::history::AsOfQuery;
+use crate::merge_patch::{merge_patch, non_null, nullable};
+use crate::pagination::{fetch_page, page_response, PageQuery};
+use crate::services::audit_service::{self, AuditOperation, ENTITY_EMP};
+use crate::services::org_service::verify_mgr;
+use crate::services::salgrade_service::find_grades;
 use crate::state::AppState;
 
 /// 構造体: emp リクエストJson
 #[derive(validator::Validate, serde::Deserialize, serde::Serialize, Debug)]
 struct EmpRequestJson {
     // ename
-    #[validate(length(min = 1, max = 10, message = "1~10文字で入力してください."))]
+    #[validate(length(min = 1, max = 10))]
     ename: String,
 
     // job
-    #[validate(length(min = 1, max = 9, message = "1~9文字で入力してください."))]
+    #[validate(length(min = 1, max = 9))]
     job: String,
 
     // mgr
@@ -27,63 +40,456 @@ struct EmpRequestJson {
     hiredate: NaiveDate,
 
     // sal
-    #[validate(range(
-        min = 0.01,
-        max = 99999.99,
-        message = "0.01 ~ 99999.99の範囲で入力してください."
-    ))]
+    #[validate(range(min = 0.01, max = 99999.99))]
     sal: f32,
 
     // comm
-    #[validate(range(
-        min = 0.01,
-        max = 99999.0,
-        message = "0.01 ~ 99999.99の範囲で入力してください."
-    ))]
+    #[validate(range(min = 0.01, max = 99999.0))]
     comm: Option<f32>,
 
     // deptno
     deptno: i32,
 }
 
-/// emp 全件取得
+/// 構造体: emp 部分更新リクエストJson（JSON Merge Patch）
+///
+/// mgr・comm は null を指定するとクリアする
+#[derive(validator::Validate, serde::Deserialize, serde::Serialize, Debug)]
+struct EmpPatchJson {
+    // ename
+    #[serde(
+        default,
+        deserialize_with = "non_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(length(min = 1, max = 10))]
+    ename: Option<String>,
+
+    // job
+    #[serde(
+        default,
+        deserialize_with = "non_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(length(min = 1, max = 9))]
+    job: Option<String>,
+
+    // mgr
+    #[serde(
+        default,
+        deserialize_with = "nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    mgr: Option<Option<i32>>,
+
+    // hiredate
+    #[serde(
+        default,
+        deserialize_with = "non_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    hiredate: Option<NaiveDate>,
+
+    // sal
+    #[serde(
+        default,
+        deserialize_with = "non_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(range(min = 0.01, max = 99999.99))]
+    sal: Option<f32>,
+
+    // comm
+    #[serde(
+        default,
+        deserialize_with = "nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(range(min = 0.01, max = 99999.0))]
+    comm: Option<Option<f32>>,
+
+    // deptno
+    #[serde(
+        default,
+        deserialize_with = "non_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    deptno: Option<i32>,
+}
+
+/// 構造体: emp 異動リクエストJson
+#[derive(serde::Deserialize, Debug)]
+struct EmpTransferJson {
+    // 異動先の deptno
+    deptno: i32,
+
+    // 異動後の mgr（未指定: 変更なし / null: クリア）
+    #[serde(default, deserialize_with = "nullable")]
+    mgr: Option<Option<i32>>,
+
+    // 発令日（記録用. 異動はリクエスト時点で反映するため未来日は不可）
+    effective_date: NaiveDate,
+
+    // true の場合は直属の部下も同じ部門に異動する
+    #[serde(default)]
+    move_reports: bool,
+}
+
+/// 構造体: emp 異動結果Json
+#[derive(serde::Serialize, Debug)]
+struct EmpTransferResultJson {
+    emp: emp::Model,
+    // 同時に異動した直属の部下
+    reports: Vec<emp::Model>,
+    effective_date: NaiveDate,
+}
+
+/// 構造体: emp 削除クエリ
+#[derive(serde::Deserialize, Debug)]
+struct EmpDeleteQuery {
+    // 直属の部下の異動先の上司（未指定の場合は部下がいると削除不可）
+    reassign_to: Option<ReassignTo>,
+}
+
+/// 部下の異動先の上司
+#[derive(Debug, Clone, Copy)]
+enum ReassignTo {
+    // 削除する社員の上司（mgr が null の場合は部下の mgr も null）
+    Parent,
+    // 指定した社員
+    Empno(i32),
+}
+
+/// "parent" または 社員コード
+impl<'de> serde::Deserialize<'de> for ReassignTo {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "parent" => Ok(ReassignTo::Parent),
+            _ => value.parse().map(ReassignTo::Empno).map_err(|_| {
+                serde::de::Error::custom(format!(
+                    "reassign_to [{}] must be 'parent' or empno.",
+                    value
+                ))
+            }),
+        }
+    }
+}
+
+/// 構造体: emp 削除結果Json
+#[derive(serde::Serialize, Debug)]
+struct EmpDeleteResultJson {
+    empno: i32,
+    // 部下の異動先の上司
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mgr: Option<i32>,
+    // 異動した部下
+    reassigned_emps: Vec<i32>,
+}
+
+/// 構造体: emp 一括登録リクエストJson（1件）
+#[derive(serde::Deserialize, Debug)]
+struct EmpBulkItemJson {
+    // empno（mode=upsert の場合のキー）
+    empno: Option<i32>,
+
+    // mgr_index（同一リクエスト内で先に登録する要素を mgr とする場合のインデックス）
+    mgr_index: Option<usize>,
+
+    #[serde(flatten)]
+    emp: EmpRequestJson,
+}
+
+/// 構造体: emp 一括登録クエリ
+#[derive(serde::Deserialize, Debug)]
+struct EmpBulkQuery {
+    // 登録モード（デフォルト: insert）
+    #[serde(default)]
+    mode: BulkMode,
+}
+
+/// 一括登録モード
+#[derive(serde::Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum BulkMode {
+    /// 登録のみ
+    #[default]
+    Insert,
+    /// empno が存在する場合は更新、存在しない場合は登録
+    Upsert,
+}
+
+/// 採番（シーケンス）補正SQL
+///
+/// * `$1` - 指定して登録した empno
+///
+/// シーケンスの値を指定した empno 以上にする（値を戻すことはない）
+const SYNC_EMPNO_SEQUENCE_SQL: &str = r#"
+SELECT setval(seq, GREATEST(nextval(seq), $1))
+FROM (SELECT pg_get_serial_sequence('emp', 'empno')::regclass AS seq) sequence
+"#;
+
+/// 制約（遅延制約トリガー）の即時チェックSQL
+///
+/// 論理削除を考慮した参照制約はコミット時にチェックされるため、要素ごと（インデックス付）にエラーとする場合・
+/// dry_run（ロールバック）でもチェックする場合に使用する
+const SET_CONSTRAINTS_IMMEDIATE_SQL: &str = "SET CONSTRAINTS ALL IMMEDIATE";
+
+/// 一括登録の上限件数
+const BULK_LIMIT: usize = 1000;
+
+/// 参照制約: emp.deptno -> dept（外部キー）
+const FK_DEPTNO: &str = "fk_deptno";
+
+/// 参照制約: emp.deptno -> dept（論理削除されていないこと. 制約トリガー）
+const FK_EMP_DEPT_ACTIVE: &str = "fk_emp_dept_active";
+
+/// 構造体: emp 表示オプション
+#[derive(serde::Deserialize, Debug)]
+struct EmpViewQuery {
+    // 等級（salgrade）の付与
+    with_grade: Option<bool>,
+}
+
+/// 構造体: emp レスポンスJson
+#[derive(serde::Serialize, Debug)]
+struct EmpResponseJson {
+    #[serde(flatten)]
+    emp: emp::Model,
+
+    // 等級（with_grade=true の場合のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grade: Option<i32>,
+}
+
+/// 構造体: emp 検索クエリ
+#[derive(validator::Validate, serde::Deserialize, Debug)]
+#[validate(schema(function = "validate_emp_search_query"))]
+struct EmpSearchQuery {
+    // job
+    #[validate(length(min = 1, max = 9))]
+    job: Option<String>,
+
+    // deptno
+    deptno: Option<i32>,
+
+    // mgr
+    mgr: Option<i32>,
+
+    // sal（下限）
+    sal_min: Option<Decimal>,
+
+    // sal（上限）
+    sal_max: Option<Decimal>,
+
+    // hiredate（開始）
+    hiredate_from: Option<NaiveDate>,
+
+    // hiredate（終了）
+    hiredate_to: Option<NaiveDate>,
+
+    // comm 有無
+    has_comm: Option<bool>,
+
+    // ソート（カンマ区切り. 先頭`-`で降順. 例: `-sal,ename`）
+    #[validate(custom(function = "validate_sort"))]
+    sort: Option<String>,
+}
+
+impl EmpSearchQuery {
+    /// 検索条件
+    fn condition(&self) -> Condition {
+        Condition::all()
+            .add_option(self.job.as_ref().map(|job| emp::Column::Job.eq(job)))
+            .add_option(self.deptno.map(|deptno| emp::Column::Deptno.eq(deptno)))
+            .add_option(self.mgr.map(|mgr| emp::Column::Mgr.eq(mgr)))
+            .add_option(self.sal_min.map(|sal| emp::Column::Sal.gte(sal)))
+            .add_option(self.sal_max.map(|sal| emp::Column::Sal.lte(sal)))
+            .add_option(
+                self.hiredate_from
+                    .map(|date| emp::Column::Hiredate.gte(date)),
+            )
+            .add_option(self.hiredate_to.map(|date| emp::Column::Hiredate.lte(date)))
+            .add_option(self.has_comm.map(|has_comm| match has_comm {
+                true => emp::Column::Comm.is_not_null(),
+                false => emp::Column::Comm.is_null(),
+            }))
+    }
+
+    /// ソート順（バリデート済であること）
+    fn orders(&self) -> Vec<(emp::Column, Order)> {
+        self.sort
+            .as_deref()
+            .and_then(|sort| parse_sort(sort).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// 範囲指定の大小チェック
+fn validate_emp_search_query(query: &EmpSearchQuery) -> Result<(), validator::ValidationError> {
+    if let (Some(min), Some(max)) = (query.sal_min, query.sal_max) {
+        if min > max {
+            return Err(validator::ValidationError::new("sal_range"));
+        }
+    }
+    if let (Some(from), Some(to)) = (query.hiredate_from, query.hiredate_to) {
+        if from > to {
+            return Err(validator::ValidationError::new("hiredate_range"));
+        }
+    }
+    Ok(())
+}
+
+/// ソート指定チェック
+fn validate_sort(sort: &str) -> Result<(), validator::ValidationError> {
+    match parse_sort(sort) {
+        Ok(_) => Ok(()),
+        Err(name) => {
+            let mut err = validator::ValidationError::new("sort");
+            err.add_param("name".into(), &name);
+            Err(err)
+        }
+    }
+}
+
+/// ソート指定を（カラム, 順序）に変換
+///
+/// 未定義のカラムの場合はカラム名を返却
+fn parse_sort(sort: &str) -> Result<Vec<(emp::Column, Order)>, String> {
+    sort.split(',')
+        .map(|item| {
+            let item = item.trim();
+            let (name, order) = match item.strip_prefix('-') {
+                Some(name) => (name, Order::Desc),
+                None => (item, Order::Asc),
+            };
+            emp::Column::from_str(name)
+                .map(|column| (column, order))
+                .map_err(|_| name.to_string())
+        })
+        .collect()
+}
+
+/// emp 一覧取得（検索・ソート・ページング）
+///
+/// 論理削除されたレコードは除く（管理者は include_deleted=true で取得可）.
+/// as_of を指定した場合はその時点の状態を返却する
 #[get("/emp")]
-async fn get_emp_all(data: web::Data<AppState>) -> Result<HttpResponse, ApiCustomError> {
-    // レコード全件取得
-    let emps = Emp::find()
-        .order_by_asc(emp::Column::Empno)
-        .all(&data.conn)
-        .await?;
+#[allow(clippy::too_many_arguments)]
+async fn get_emp_all(
+    req: HttpRequest,
+    ctx: RequestContext,
+    query: Result<actix_web::web::Query<EmpSearchQuery>, actix_web::Error>,
+    page: Result<actix_web::web::Query<PageQuery>, actix_web::Error>,
+    view: Result<actix_web::web::Query<EmpViewQuery>, actix_web::Error>,
+    deleted_query: Result<actix_web::web::Query<DeletedQuery>, actix_web::Error>,
+    as_of_query: Result<actix_web::web::Query<AsOfQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋バリデート）
+    let query = query?.into_inner();
+    query.validate()?;
+    let page = page?.into_inner();
+    page.validate()?;
+    let view = view?.into_inner();
+    let include_deleted = deleted_query?.include_deleted(&ctx)?;
+    let as_of = as_of_query?.into_inner().as_of;
 
-    // レスポンス
-    if emps.is_empty() {
-        return Ok(HttpResponse::NoContent().finish());
+    // キーセットページングはキー順のみ
+    if query.sort.is_some() && page.after.is_some() {
+        let mut errors = validator::ValidationErrors::new();
+        errors.add("sort", validator::ValidationError::new("sort_with_after"));
+        return Err(errors.into());
+    }
+
+    // 検索条件・ソート
+    let select = match as_of {
+        Some(as_of) => Emp::find_as_of(as_of),
+        None => Emp::find(),
+    };
+    let select = match include_deleted {
+        true => select,
+        false => select.filter(emp::Column::DeletedAt.is_null()),
+    };
+    let mut select = select.filter(query.condition());
+    for (column, order) in query.orders() {
+        select = select.order_by(column, order);
     }
-    Ok(HttpResponse::Ok().json(emps))
+
+    // レコード取得（ページング）
+    let emps = fetch_page(&data.conn, select, emp::Column::Empno, &page).await?;
+
+    // 等級の付与
+    let grades = match view.with_grade {
+        Some(true) => {
+            let empnos = emps.items.iter().map(|emp| emp.empno).collect();
+            find_grades(&data.conn, as_of, empnos).await?
+        }
+        _ => HashMap::new(),
+    };
+    let emps = emps.map(|emp| EmpResponseJson {
+        grade: grades.get(&emp.empno).copied(),
+        emp,
+    });
+
+    // レスポンス
+    Ok(page_response(&req, &emps))
 }
 
 /// emp キー取得
+///
+/// 論理削除されたレコードは除く（管理者は include_deleted=true で取得可）.
+/// as_of を指定した場合はその時点の状態を返却する（ETag なし）
 #[get("/emp/{empno}")]
 async fn get_emp_by_key(
+    ctx: RequestContext,
     path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    view: Result<actix_web::web::Query<EmpViewQuery>, actix_web::Error>,
+    deleted_query: Result<actix_web::web::Query<DeletedQuery>, actix_web::Error>,
+    as_of_query: Result<actix_web::web::Query<AsOfQuery>, actix_web::Error>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiCustomError> {
     // クエリストリング取得（＋型チェックと型変換）
     let empno: i32 = path?.into_inner().try_into().unwrap();
+    let view = view?.into_inner();
+    let include_deleted = deleted_query?.include_deleted(&ctx)?;
+    let as_of = as_of_query?.into_inner().as_of;
 
     // キーに該当するレコードを取得する
-    let emp = Emp::find_by_id(empno).one(&data.conn).await?;
+    let select = match as_of {
+        Some(as_of) => Emp::find_as_of(as_of).filter(emp::Column::Empno.eq(empno)),
+        None => Emp::find_by_id(empno),
+    };
+    let select = match include_deleted {
+        true => select,
+        false => select.filter(emp::Column::DeletedAt.is_null()),
+    };
+    let emp = match select.one(&data.conn).await? {
+        Some(emp) => emp,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+
+    // 等級の付与
+    let grade = match view.with_grade {
+        Some(true) => find_grades(&data.conn, as_of, vec![empno])
+            .await?
+            .remove(&empno),
+        _ => None,
+    };
 
     // レスポンス
-    match emp {
-        Some(emp) => Ok(HttpResponse::Ok().json(emp)),
-        _ => Err(ApiCustomError::NotFound),
+    match as_of {
+        Some(_) => Ok(HttpResponse::Ok().json(EmpResponseJson { emp, grade })),
+        None => Ok(HttpResponse::Ok()
+            .insert_header(header::ETag(etag(emp.version)))
+            .json(EmpResponseJson { emp, grade })),
     }
 }
 
 /// emp 登録
 #[post("/emp")]
 async fn post_emp(
+    ctx: RequestContext,
     form: Result<actix_web::web::Json<EmpRequestJson>, actix_web::Error>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiCustomError> {
@@ -91,37 +497,61 @@ async fn post_emp(
     let form = form?;
     form.validate()?;
 
-    // 親レコードチェック
-    if !exists_dept(&data.conn, form.deptno).await? {
-        return Err(ApiCustomError::UnporcessibleEntity(format!(
-            "deptno [{}] is not exists.",
-            form.deptno
-        )));
-    }
+    // DB登録（トランザクション）
+    let txn = data.conn.begin().await?;
+    let emp = insert_emp(&txn, &ctx, None, &form).await?;
+    txn.commit().await?;
 
-    // mgrチェック
-    // ... mgr（上司の社員コード） はempテーブル上存在していること
-    if form.mgr.is_some() && !exists_emp(&data.conn, form.mgr.unwrap()).await? {
-        return Err(ApiCustomError::UnporcessibleEntity(format!(
-            "mgr(empno) [{}] is not exists.",
-            form.mgr.unwrap()
-        )));
-    }
+    // レスポンス
+    Ok(HttpResponse::Created().json(emp))
+}
 
-    // DB登録
-    let emp = emp::ActiveModel::from_json(json!(form))?
-        .insert(&data.conn)
-        .await?;
+/// emp 変更（全項目）
+///
+/// If-Match（ETag）が現在の version と一致しない場合は 412
+#[put("/emp/{empno}")]
+async fn put_emp(
+    req: HttpRequest,
+    ctx: RequestContext,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    form: Result<actix_web::web::Json<EmpRequestJson>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let empno: i32 = path?.into_inner().try_into().unwrap();
+
+    // バリデート
+    let form = form?;
+    form.validate()?;
+
+    // 更新（mgrチェック込. トランザクション）
+    let txn = data.conn.begin().await?;
+    let emp = Emp::find_active_by_id(empno).one(&txn).await?;
+    let updated_emp = match emp {
+        Some(emp) => {
+            verify_if_match(&req, emp.version, data.require_if_match)?;
+            update_emp(&txn, &ctx, emp, &form).await?
+        }
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    txn.commit().await?;
 
     // レスポンス
-    Ok(HttpResponse::Created().json(emp))
+    Ok(HttpResponse::Ok()
+        .insert_header(header::ETag(etag(updated_emp.version)))
+        .json(updated_emp))
 }
 
-/// emp 変更
+/// emp 変更（部分更新. JSON Merge Patch）
+///
+/// 指定された項目のみバリデート・更新する（mgr・comm は null でクリア）.
+/// If-Match（ETag）が現在の version と一致しない場合は 412
 #[patch("/emp/{empno}")]
 async fn patch_emp(
+    req: HttpRequest,
+    ctx: RequestContext,
     path: Result<actix_web::web::Path<u32>, actix_web::Error>,
-    form: Result<actix_web::web::Json<EmpRequestJson>, actix_web::Error>,
+    form: Result<actix_web::web::Json<EmpPatchJson>, actix_web::Error>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiCustomError> {
     // クエリストリング取得（＋型チェックと型変換）
@@ -130,77 +560,707 @@ async fn patch_emp(
     // バリデート
     let form = form?;
     form.validate()?;
+    let patch = json!(form).as_object().cloned().unwrap_or_default();
 
-    // 親レコードチェック
-    if !exists_dept(&data.conn, form.deptno).await? {
-        return Err(ApiCustomError::UnporcessibleEntity(format!(
-            "deptno [{}] is not exists.",
-            form.deptno
-        )));
-    }
-
-    // 更新
-    let emp = Emp::find_by_id(empno).one(&data.conn).await?;
+    // 更新（mgrチェック込. トランザクション）
+    let txn = data.conn.begin().await?;
+    let emp = Emp::find_active_by_id(empno).one(&txn).await?;
     let updated_emp = match emp {
         Some(emp) => {
-            let mut emp_active_model = emp.into_active_model();
-            emp_active_model.set_from_json(json!(form))?;
-            emp_active_model.update(&data.conn).await?
+            verify_if_match(&req, emp.version, data.require_if_match)?;
+            if let Some(Some(mgr)) = form.mgr {
+                verify_mgr(&txn, Some(empno), mgr).await?;
+            }
+            let emp_active_model = merge_patch(emp.clone(), &patch)?;
+            save_emp(&txn, &ctx, &emp, emp_active_model, AuditOperation::Update).await?
         }
         _ => return Err(ApiCustomError::NotFound),
     };
+    txn.commit().await?;
 
     // レスポンス
-    Ok(HttpResponse::Ok().json(updated_emp))
+    Ok(HttpResponse::Ok()
+        .insert_header(header::ETag(etag(updated_emp.version)))
+        .json(updated_emp))
 }
 
+/// emp 削除（論理削除）
+///
+/// 直属の部下がいる場合は reassign_to を指定する（部下の mgr を変更してから削除する. 1トランザクション）.
+/// * 未指定 - 削除不可（参照制約 fk_emp_mgr_active 違反）
+/// * `parent` - 削除する社員の上司
+/// * 社員コード - 指定した社員（削除する社員自身・配下の社員は不可）
+///
+/// If-Match（ETag）が現在の version と一致しない場合は 412
 #[delete("/emp/{empno}")]
 async fn delete_emp(
+    req: HttpRequest,
+    ctx: RequestContext,
     path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    query: Result<actix_web::web::Query<EmpDeleteQuery>, actix_web::Error>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, ApiCustomError> {
     // クエリストリング取得（型チェック込）
     let empno: i32 = path?.into_inner().try_into().unwrap();
+    let reassign_to = query?.into_inner().reassign_to;
+
+    // トランザクション
+    let txn = data.conn.begin().await?;
+
+    // バージョンチェック
+    let emp = match Emp::find_active_by_id(empno).one(&txn).await? {
+        Some(emp) => emp,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    verify_if_match(&req, emp.version, data.require_if_match)?;
 
-    // 削除対象empnoと同じ値のmgrのレコードが存在する場合は削除不可
-    if exists_emp_mgr(&data.conn, empno).await? {
-        return Err(ApiCustomError::UnporcessibleEntity(format!(
-            "empno [{}] can not delete.",
-            empno
-        )));
+    // 部下の異動先の上司
+    let mgr = match reassign_to {
+        Some(ReassignTo::Parent) => emp.mgr,
+        Some(ReassignTo::Empno(mgr)) => {
+            // 存在・自分自身・配下（循環）チェック
+            verify_mgr(&txn, Some(empno), mgr).await?;
+            Some(mgr)
+        }
+        None => None,
+    };
+
+    // 直属の部下の異動
+    // （reassign_to 未指定で部下が存在する場合はコミット時に参照制約 fk_emp_mgr_active 違反）
+    let reports = match reassign_to {
+        Some(_) => {
+            Emp::find_active()
+                .filter(emp::Column::Mgr.eq(empno))
+                .order_by_asc(emp::Column::Empno)
+                .all(&txn)
+                .await?
+        }
+        None => Vec::new(),
+    };
+    let mut reassigned_emps = Vec::with_capacity(reports.len());
+    for report in reports {
+        let mut report_active_model = report.clone().into_active_model();
+        report_active_model.mgr = Set(mgr);
+        save_emp(
+            &txn,
+            &ctx,
+            &report,
+            report_active_model,
+            AuditOperation::Update,
+        )
+        .await?;
+        reassigned_emps.push(report.empno);
     }
 
-    // キーに該当するレコードを削除する
-    let result = Emp::delete_by_id(empno).exec(&data.conn).await?;
+    // 削除日時を設定する
+    let mut emp_active_model = emp.clone().into_active_model();
+    emp_active_model.deleted_at = Set(Some(Utc::now().into()));
+    save_emp(&txn, &ctx, &emp, emp_active_model, AuditOperation::Delete).await?;
+    txn.commit().await?;
 
     // レスポンス
-    if result.rows_affected == 0 {
-        return Err(ApiCustomError::NotFound);
+    match reassign_to {
+        Some(_) => Ok(HttpResponse::Ok().json(EmpDeleteResultJson {
+            empno,
+            mgr,
+            reassigned_emps,
+        })),
+        None => Ok(HttpResponse::NoContent().finish()),
     }
-    Ok(HttpResponse::NoContent().finish())
 }
 
-/// 参照制約チェック（親レコード有無）
+/// emp 復元（論理削除の取消）
+///
+/// 復元時点で deptno・mgr の参照先が存在（未削除）していること（参照制約）.
+/// If-Match（ETag）が現在の version と一致しない場合は 412
+#[post("/emp/{empno}/restore")]
+async fn post_emp_restore(
+    req: HttpRequest,
+    ctx: RequestContext,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（型チェック込）
+    let empno: i32 = path?.into_inner().try_into().unwrap();
+
+    // 削除チェック
+    let txn = data.conn.begin().await?;
+    let emp = match Emp::find_by_id(empno).one(&txn).await? {
+        Some(emp) => emp,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    if emp.deleted_at.is_none() {
+        return Err(ApiCustomError::unprocessable(
+            "emp_not_deleted",
+            [("empno", json!(empno))],
+        ));
+    }
+    verify_if_match(&req, emp.version, data.require_if_match)?;
+
+    // mgrチェック（循環）
+    if let Some(mgr) = emp.mgr {
+        verify_mgr(&txn, Some(empno), mgr).await?;
+    }
+
+    // 削除日時をクリアする
+    let mut emp_active_model = emp.clone().into_active_model();
+    emp_active_model.deleted_at = Set(None);
+    let restored_emp =
+        save_emp(&txn, &ctx, &emp, emp_active_model, AuditOperation::Restore).await?;
+    txn.commit().await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok()
+        .insert_header(header::ETag(etag(restored_emp.version)))
+        .json(restored_emp))
+}
+
+/// emp 異動
 ///
-/// 引数のdeptnoが設定されたdeptテーブルの有無を返却
-async fn exists_dept(conn: &DatabaseConnection, deptno: i32) -> Result<bool, DbErr> {
-    Ok(Dept::find_by_id(deptno).one(conn).await?.is_some())
+/// 部門（＋上司）を変更する. move_reports=true の場合は直属の部下の部門も変更する（上司は変更しない）.
+/// 発令日は監査ログ・レスポンスに記録する. 異動はリクエスト時点で反映する（履歴の有効期間・as_of も処理日時基準）ため、
+/// 発令日は入社日以降・当日以前であること（未来日の予約は不可）.
+/// If-Match（ETag）が現在の version と一致しない場合は 412
+#[post("/emp/{empno}/transfer")]
+async fn post_emp_transfer(
+    req: HttpRequest,
+    ctx: RequestContext,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    form: Result<actix_web::web::Json<EmpTransferJson>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let empno: i32 = path?.into_inner().try_into().unwrap();
+
+    // バリデート
+    let form = form?.into_inner();
+    if form.effective_date > Utc::now().date_naive() {
+        let mut errors = validator::ValidationErrors::new();
+        errors.add(
+            "effective_date",
+            validator::ValidationError::new("future_date"),
+        );
+        return Err(errors.into());
+    }
+
+    // トランザクション
+    let txn = data.conn.begin().await?;
+
+    // 対象レコード取得
+    let emp = match Emp::find_active_by_id(empno).one(&txn).await? {
+        Some(emp) => emp,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    verify_if_match(&req, emp.version, data.require_if_match)?;
+    if form.effective_date < emp.hiredate {
+        return Err(ApiCustomError::unprocessable(
+            "effective_date_before_hiredate",
+            [
+                ("effective_date", json!(form.effective_date)),
+                ("hiredate", json!(emp.hiredate)),
+            ],
+        ));
+    }
+
+    // mgrチェック（変更する場合のみ）
+    if let Some(Some(mgr)) = form.mgr {
+        verify_mgr(&txn, Some(empno), mgr).await?;
+    }
+
+    // 異動
+    let mut emp_active_model = emp.clone().into_active_model();
+    emp_active_model.deptno = Set(form.deptno);
+    if let Some(mgr) = form.mgr {
+        emp_active_model.mgr = Set(mgr);
+    }
+    let transferred_emp =
+        transfer_emp(&txn, &ctx, &emp, emp_active_model, form.effective_date).await?;
+
+    // 直属の部下の異動
+    let mut reports = Vec::new();
+    if form.move_reports {
+        let direct_reports = Emp::find_active()
+            .filter(emp::Column::Mgr.eq(empno))
+            .order_by_asc(emp::Column::Empno)
+            .all(&txn)
+            .await?;
+        for report in direct_reports {
+            let mut report_active_model = report.clone().into_active_model();
+            report_active_model.deptno = Set(form.deptno);
+            reports.push(
+                transfer_emp(
+                    &txn,
+                    &ctx,
+                    &report,
+                    report_active_model,
+                    form.effective_date,
+                )
+                .await?,
+            );
+        }
+    }
+    txn.commit().await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok()
+        .insert_header(header::ETag(etag(transferred_emp.version)))
+        .json(EmpTransferResultJson {
+            emp: transferred_emp,
+            reports,
+            effective_date: form.effective_date,
+        }))
 }
 
-/// empno存在チェック
+/// dept配下 emp 一覧取得（ページング）
+#[get("/dept/{deptno}/emp")]
+async fn get_dept_emp_all(
+    req: HttpRequest,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    page: Result<actix_web::web::Query<PageQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let deptno: i32 = path?.into_inner().try_into().unwrap();
+    let page = page?.into_inner();
+    page.validate()?;
+
+    // 親レコード取得
+    let dept = match Dept::find_active_by_id(deptno).one(&data.conn).await? {
+        Some(dept) => dept,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+
+    // レコード取得（dept -> emp のリレーション）
+    let emps = fetch_page(
+        &data.conn,
+        dept.find_related(Emp)
+            .filter(emp::Column::DeletedAt.is_null()),
+        emp::Column::Empno,
+        &page,
+    )
+    .await?;
+
+    // レスポンス
+    Ok(page_response(&req, &emps))
+}
+
+/// dept配下 emp 登録
 ///
-/// 引数のempnoに紐づくempテーブルレコードの有無を返却
-async fn exists_emp(conn: &DatabaseConnection, empno: i32) -> Result<bool, DbErr> {
-    Ok(Emp::find_by_id(empno).one(conn).await?.is_some())
+/// deptno はパスの値を使用する（リクエストボディの deptno は上書き）
+#[post("/dept/{deptno}/emp")]
+async fn post_dept_emp(
+    ctx: RequestContext,
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    form: Result<
+        actix_web::web::Json<serde_json::Map<String, serde_json::Value>>,
+        actix_web::Error,
+    >,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let deptno: i32 = path?.into_inner().try_into().unwrap();
+
+    // バリデート
+    let mut form = form?.into_inner();
+    form.insert("deptno".to_string(), json!(deptno));
+    let form: EmpRequestJson = serde_json::from_value(serde_json::Value::Object(form))
+        .map_err(actix_web::error::ErrorBadRequest)?;
+    form.validate()?;
+
+    // DB登録（トランザクション. 親レコードが存在しない場合は 404）
+    let txn = data.conn.begin().await?;
+    let emp = insert_emp(&txn, &ctx, None, &form)
+        .await
+        .map_err(dept_not_found)?;
+    txn.commit()
+        .await
+        .map_err(|err| dept_not_found(err.into()))?;
+
+    // レスポンス
+    Ok(HttpResponse::Created().json(emp))
 }
 
-/// empno mgr存在チェック
+/// 親レコード（dept）の参照制約違反 -> 404
+fn dept_not_found(err: ApiCustomError) -> ApiCustomError {
+    match err {
+        ApiCustomError::ConstraintViolation(constraint)
+            if constraint == FK_DEPTNO || constraint == FK_EMP_DEPT_ACTIVE =>
+        {
+            ApiCustomError::NotFound
+        }
+        err => err,
+    }
+}
+
+/// emp 一括登録
 ///
-/// 引数のempnoと同じ値が設定されているempテーブル.mgr のレコードの有無を返却
-async fn exists_emp_mgr(conn: &DatabaseConnection, empno: i32) -> Result<bool, DbErr> {
-    Ok(Emp::find()
-        .filter(emp::Column::Mgr.eq(empno))
-        .one(conn)
-        .await?
-        .is_some())
+/// 全件をバリデートした上で、1トランザクションで登録する（1件でもエラーの場合は全件ロールバック）.
+/// mode=upsert の場合、empno が指定された要素は既存レコードを更新する（存在しない場合はその empno で登録）.
+#[post("/emp/bulk")]
+async fn post_emp_bulk(
+    ctx: RequestContext,
+    query: Result<actix_web::web::Query<EmpBulkQuery>, actix_web::Error>,
+    form: Result<actix_web::web::Json<Vec<EmpBulkItemJson>>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得
+    let mode = query?.into_inner().mode;
+
+    // バリデート（全件）
+    let items = form?.into_inner();
+    if items.is_empty() || items.len() > BULK_LIMIT {
+        let mut length_error = validator::ValidationError::new("bulk_length");
+        length_error.add_param("min".into(), &1);
+        length_error.add_param("max".into(), &BULK_LIMIT);
+        let mut errors = validator::ValidationErrors::new();
+        errors.add("items", length_error);
+        return Err(errors.into());
+    }
+    let row_errors: Vec<(usize, validator::ValidationErrors)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            validate_bulk_item(index, item, &mode)
+                .err()
+                .map(|err| (index, err))
+        })
+        .collect();
+    if !row_errors.is_empty() {
+        return Err(ApiCustomError::RowValidationError(row_errors));
+    }
+
+    // DB登録（トランザクション）
+    let txn = data.conn.begin().await?;
+    let emps = save_emps(&txn, &ctx, items, &mode).await?;
+    txn.commit().await?;
+
+    // レスポンス
+    match mode {
+        BulkMode::Insert => Ok(HttpResponse::Created().json(emps)),
+        BulkMode::Upsert => Ok(HttpResponse::Ok().json(emps)),
+    }
+}
+
+/// emp 一括登録（DB）
+///
+/// 要素ごとのエラーはインデックスを付与して返却する.
+/// 参照制約（論理削除を考慮）も要素ごとにチェックする（コミットまで遅延させない）
+async fn save_emps<C: ConnectionTrait>(
+    conn: &C,
+    ctx: &RequestContext,
+    items: Vec<EmpBulkItemJson>,
+    mode: &BulkMode,
+) -> Result<Vec<emp::Model>, ApiCustomError> {
+    conn.execute_unprepared(SET_CONSTRAINTS_IMMEDIATE_SQL)
+        .await?;
+
+    let mut emps: Vec<emp::Model> = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        let mut form = item.emp;
+
+        // mgr_index -> 先に登録した要素の empno
+        if let Some(mgr_index) = item.mgr_index {
+            form.mgr = Some(emps[mgr_index].empno);
+        }
+
+        // 登録・更新
+        let emp = match (mode, item.empno) {
+            (BulkMode::Upsert, Some(empno)) => match Emp::find_by_id(empno).one(conn).await? {
+                Some(emp) if emp.deleted_at.is_some() => Err(ApiCustomError::unprocessable(
+                    "emp_deleted",
+                    [("empno", json!(empno))],
+                )),
+                Some(emp) => update_emp(conn, ctx, emp, &form).await,
+                _ => insert_emp(conn, ctx, Some(empno), &form).await,
+            },
+            _ => insert_emp(conn, ctx, None, &form).await,
+        }
+        .map_err(|err| ApiCustomError::RowError(index, Box::new(err)))?;
+        emps.push(emp);
+    }
+    Ok(emps)
+}
+
+/// 一括登録の要素のバリデート
+///
+/// mgr_index は先行する要素を指すこと. empno は mode=upsert の場合のみ指定可.
+fn validate_bulk_item(
+    index: usize,
+    item: &EmpBulkItemJson,
+    mode: &BulkMode,
+) -> Result<(), validator::ValidationErrors> {
+    let mut errors = match item.emp.validate() {
+        Ok(_) => validator::ValidationErrors::new(),
+        Err(errors) => errors,
+    };
+    if item.empno.is_some() && *mode != BulkMode::Upsert {
+        errors.add(
+            "empno",
+            validator::ValidationError::new("empno_not_allowed"),
+        );
+    }
+    if let Some(mgr_index) = item.mgr_index {
+        if mgr_index >= index || item.emp.mgr.is_some() {
+            errors.add("mgr_index", validator::ValidationError::new("mgr_index"));
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+/// emp CSVエクスポート
+#[get("/emp.csv")]
+async fn get_emp_csv(data: web::Data<AppState>) -> Result<HttpResponse, ApiCustomError> {
+    // レスポンス（全件. チャンクで取得・返却する）
+    csv_response(
+        data.conn.clone(),
+        Emp::find_active(),
+        emp::Column::Empno,
+        "emp.csv",
+        &[
+            "empno", "ename", "job", "mgr", "hiredate", "sal", "comm", "deptno",
+        ],
+        |emp| {
+            vec![
+                emp.empno.to_string(),
+                emp.ename,
+                emp.job,
+                emp.mgr.map(|mgr| mgr.to_string()).unwrap_or_default(),
+                emp.hiredate.to_string(),
+                emp.sal.to_string(),
+                emp.comm.map(|comm| comm.to_string()).unwrap_or_default(),
+                emp.deptno.to_string(),
+            ]
+        },
+    )
+}
+
+/// emp CSVインポート
+///
+/// ヘッダ行: ename,job,mgr,hiredate,sal,comm,deptno.
+/// 全行を1トランザクションで登録する（dry_run=true の場合はロールバック）
+#[post("/emp/import")]
+async fn post_emp_import(
+    ctx: RequestContext,
+    query: Result<actix_web::web::Query<ImportQuery>, actix_web::Error>,
+    body: web::Bytes,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得
+    let dry_run = query?.into_inner().dry_run;
+
+    // CSV読込（＋バリデート）
+    let forms: Vec<EmpRequestJson> = read_csv(&body)?;
+    let rows = forms.len();
+
+    // DB登録（トランザクション）
+    let txn = data.conn.begin().await?;
+    let items = forms
+        .into_iter()
+        .map(|emp| EmpBulkItemJson {
+            empno: None,
+            mgr_index: None,
+            emp,
+        })
+        .collect();
+    save_emps(&txn, &ctx, items, &BulkMode::Insert).await?;
+    match dry_run {
+        true => txn.rollback().await?,
+        false => txn.commit().await?,
+    }
+
+    // レスポンス
+    let result = ImportResultJson { rows, dry_run };
+    match dry_run {
+        true => Ok(HttpResponse::Ok().json(result)),
+        false => Ok(HttpResponse::Created().json(result)),
+    }
+}
+
+/// emp 登録（DB）
+///
+/// * `empno` - 社員コード（None の場合は採番）
+///
+/// deptno・mgr の参照先の存在は参照制約でチェックする（新規登録のため循環はしない）
+async fn insert_emp<C: ConnectionTrait>(
+    conn: &C,
+    ctx: &RequestContext,
+    empno: Option<i32>,
+    form: &EmpRequestJson,
+) -> Result<emp::Model, ApiCustomError> {
+    // DB登録
+    let mut emp_active_model = emp::ActiveModel::from_json(json!(form))?;
+    if let Some(empno) = empno {
+        emp_active_model.empno = Set(empno);
+    }
+    let emp = emp_active_model.insert(conn).await?;
+
+    // empno を指定して登録した場合の採番（シーケンス）補正（以降の採番と重複させない）
+    if let Some(empno) = empno {
+        conn.execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            SYNC_EMPNO_SEQUENCE_SQL,
+            [empno.into()],
+        ))
+        .await?;
+    }
+
+    // 監査ログ
+    audit_service::record(
+        conn,
+        ctx,
+        ENTITY_EMP,
+        emp.empno,
+        AuditOperation::Insert,
+        None,
+        Some(&emp),
+    )
+    .await?;
+    Ok(emp)
+}
+
+/// emp 更新（DB）
+///
+/// mgr（上司の社員コード） は存在し、自分自身・循環とならないこと
+async fn update_emp<C: ConnectionTrait>(
+    conn: &C,
+    ctx: &RequestContext,
+    emp: emp::Model,
+    form: &EmpRequestJson,
+) -> Result<emp::Model, ApiCustomError> {
+    // mgrチェック
+    if let Some(mgr) = form.mgr {
+        verify_mgr(conn, Some(emp.empno), mgr).await?;
+    }
+
+    // 更新
+    let mut emp_active_model = emp.clone().into_active_model();
+    emp_active_model.set_from_json(json!(form))?;
+    save_emp(conn, ctx, &emp, emp_active_model, AuditOperation::Update).await
+}
+
+/// emp 更新（DB）
+///
+/// * `emp` - 更新前のレコード
+///
+/// version をインクリメントし、監査ログを記録する
+pub(crate) async fn save_emp<C: ConnectionTrait>(
+    conn: &C,
+    ctx: &RequestContext,
+    emp: &emp::Model,
+    emp_active_model: emp::ActiveModel,
+    operation: AuditOperation,
+) -> Result<emp::Model, ApiCustomError> {
+    let updated_emp = update_version(conn, emp, emp_active_model).await?;
+
+    // 監査ログ
+    audit_service::record(
+        conn,
+        ctx,
+        ENTITY_EMP,
+        emp.empno,
+        operation,
+        Some(emp),
+        Some(&updated_emp),
+    )
+    .await?;
+    Ok(updated_emp)
+}
+
+/// emp 異動（DB）
+///
+/// * `emp` - 更新前のレコード
+///
+/// 監査ログ（更新後）に発令日を付与する
+async fn transfer_emp<C: ConnectionTrait>(
+    conn: &C,
+    ctx: &RequestContext,
+    emp: &emp::Model,
+    emp_active_model: emp::ActiveModel,
+    effective_date: NaiveDate,
+) -> Result<emp::Model, ApiCustomError> {
+    let transferred_emp = update_version(conn, emp, emp_active_model).await?;
+
+    // 監査ログ
+    let mut after = json!(transferred_emp);
+    after["effective_date"] = json!(effective_date);
+    audit_service::record(
+        conn,
+        ctx,
+        ENTITY_EMP,
+        emp.empno,
+        AuditOperation::Transfer,
+        Some(&json!(emp)),
+        Some(&after),
+    )
+    .await?;
+    Ok(transferred_emp)
+}
+
+/// emp 更新（DB. バージョンチェック）
+///
+/// version をインクリメントする. 取得時から version が変わっている（他で更新された）場合は 412
+async fn update_version<C: ConnectionTrait>(
+    conn: &C,
+    emp: &emp::Model,
+    mut emp_active_model: emp::ActiveModel,
+) -> Result<emp::Model, ApiCustomError> {
+    emp_active_model.version = Set(emp.version + 1);
+    Emp::update(emp_active_model)
+        .filter(emp::Column::Version.eq(emp.version))
+        .exec(conn)
+        .await
+        .map_err(stale_to_precondition_failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sort(sort: &str) -> Result<String, String> {
+        parse_sort(sort).map(|items| format!("{:?}", items))
+    }
+
+    #[test]
+    fn parse_sort_asc_and_desc() {
+        assert_eq!(
+            sort("deptno,-sal"),
+            Ok(format!(
+                "{:?}",
+                vec![
+                    (emp::Column::Deptno, Order::Asc),
+                    (emp::Column::Sal, Order::Desc)
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_sort_trims_items() {
+        assert_eq!(
+            sort(" ename , -hiredate "),
+            Ok(format!(
+                "{:?}",
+                vec![
+                    (emp::Column::Ename, Order::Asc),
+                    (emp::Column::Hiredate, Order::Desc)
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_sort_unknown_column() {
+        assert_eq!(sort("ename,-password"), Err("password".to_string()));
+    }
+
+    #[test]
+    fn parse_sort_rejects_bare_prefix_and_empty_item() {
+        assert_eq!(sort("-"), Err("".to_string()));
+        assert_eq!(sort("ename,"), Err("".to_string()));
+        assert_eq!(sort("--sal"), Err("-sal".to_string()));
+    }
 }
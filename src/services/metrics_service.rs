@@ -0,0 +1,20 @@
+use actix_web::{get, web, HttpResponse};
+
+use crate::error::ApiCustomError;
+use crate::metrics::METRICS;
+use crate::state::AppState;
+
+/// Content-Type（Prometheus text exposition format）
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// メトリクス取得（Prometheus）
+#[get("/metrics")]
+async fn get_metrics(data: web::Data<AppState>) -> Result<HttpResponse, ApiCustomError> {
+    // メトリクス出力
+    let body = METRICS.render(&data.conn)?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok()
+        .content_type(METRICS_CONTENT_TYPE)
+        .body(body))
+}
@@ -0,0 +1,282 @@
+use actix_web::{get, web, HttpResponse};
+use sea_orm::*;
+use serde_json::json;
+use std::collections::HashMap;
+use validator::Validate;
+
+use crate::error::ApiCustomError;
+use crate::state::AppState;
+
+/// 部下（直属・間接）取得SQL
+///
+/// 起点の社員を level 0 とし、emp.mgr を辿って配下の社員を取得する.
+/// データ不整合（循環）があっても停止するよう、経路（path）上の社員は再訪しない.
+/// 論理削除された社員は含まない.
+const REPORTS_SQL: &str = r#"
+WITH RECURSIVE reports AS (
+    SELECT empno, ename, job, mgr, 0 AS level, ARRAY[empno] AS path
+    FROM emp
+    WHERE empno = $1 AND deleted_at IS NULL
+  UNION ALL
+    SELECT e.empno, e.ename, e.job, e.mgr, r.level + 1, r.path || e.empno
+    FROM emp e
+    JOIN reports r ON e.mgr = r.empno
+    WHERE NOT e.empno = ANY(r.path) AND e.deleted_at IS NULL
+)
+SELECT empno, ename, job, mgr, level FROM reports ORDER BY level, empno
+"#;
+
+/// 上司の連鎖取得SQL
+///
+/// 起点の社員を level 0 とし、emp.mgr を上位に辿る.
+/// 経路（path）上の社員に戻った場合は is_cycle = true として打ち切る.
+/// 論理削除された社員は存在しないものとして扱う.
+const CHAIN_SQL: &str = r#"
+WITH RECURSIVE chain AS (
+    SELECT empno, ename, job, mgr, 0 AS level, ARRAY[empno] AS path, false AS is_cycle
+    FROM emp
+    WHERE empno = $1 AND deleted_at IS NULL
+  UNION ALL
+    SELECT e.empno, e.ename, e.job, e.mgr, c.level + 1, c.path || e.empno, e.empno = ANY(c.path)
+    FROM emp e
+    JOIN chain c ON e.empno = c.mgr
+    WHERE NOT c.is_cycle AND e.deleted_at IS NULL
+)
+SELECT empno, ename, job, mgr, level, is_cycle FROM chain ORDER BY level
+"#;
+
+/// 上司変更の排他SQL（トランザクション終了まで保持するアドバイザリロック）
+///
+/// * `$1` - ロックキー（MGR_LOCK_KEY）
+const MGR_LOCK_SQL: &str = "SELECT pg_advisory_xact_lock($1)";
+
+/// 上司変更の排他のロックキー（任意の固定値）
+const MGR_LOCK_KEY: i64 = 0x0065_6d70_5f6d_6772; // "emp_mgr"
+
+/// 構造体: 部下取得クエリ
+#[derive(validator::Validate, serde::Deserialize, Debug)]
+struct ReportsQuery {
+    // 階層の深さ（未指定の場合は全階層）
+    #[validate(range(min = 1, max = 100))]
+    depth: Option<u32>,
+}
+
+/// 構造体: 部下（SQL取得結果）
+#[derive(FromQueryResult, Debug)]
+struct ReportRow {
+    empno: i32,
+    ename: String,
+    job: String,
+    mgr: Option<i32>,
+    level: i32,
+}
+
+/// 構造体: 組織図ノード
+#[derive(serde::Serialize, Debug)]
+struct ReportNode {
+    empno: i32,
+    ename: String,
+    job: String,
+    level: i32,
+    // 配下の人数（直属・間接. depth に関わらず全階層）
+    headcount: usize,
+    reports: Vec<ReportNode>,
+}
+
+/// 構造体: 上司（SQL取得結果）
+#[derive(FromQueryResult, serde::Serialize, Debug)]
+pub(crate) struct ChainRow {
+    pub empno: i32,
+    pub ename: String,
+    pub job: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mgr: Option<i32>,
+    pub level: i32,
+    #[serde(skip)]
+    pub is_cycle: bool,
+}
+
+/// 部下（組織図）取得
+#[get("/emp/{empno}/reports")]
+async fn get_emp_reports(
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    query: Result<actix_web::web::Query<ReportsQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let empno: i32 = path?.into_inner().try_into().unwrap();
+    let query = query?.into_inner();
+    query.validate()?;
+
+    // 配下の社員を取得する
+    let rows = ReportRow::find_by_statement(Statement::from_sql_and_values(
+        data.conn.get_database_backend(),
+        REPORTS_SQL,
+        [empno.into()],
+    ))
+    .all(&data.conn)
+    .await?;
+
+    // 組織図（ツリー）に変換
+    let mut rows = rows.into_iter();
+    let root = match rows.next() {
+        Some(root) => root,
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    let mut children: HashMap<i32, Vec<ReportRow>> = HashMap::new();
+    for row in rows {
+        if let Some(mgr) = row.mgr {
+            children.entry(mgr).or_default().push(row);
+        }
+    }
+
+    // レスポンス
+    Ok(HttpResponse::Ok().json(build_node(root, &mut children, query.depth)))
+}
+
+/// 組織図ノード生成
+///
+/// * `depth` - 残りの階層数（None は無制限）. 0 の場合は配下ノードを出力しない
+fn build_node(
+    row: ReportRow,
+    children: &mut HashMap<i32, Vec<ReportRow>>,
+    depth: Option<u32>,
+) -> ReportNode {
+    let reports: Vec<ReportNode> = children
+        .remove(&row.empno)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|child| build_node(child, children, depth.map(|depth| depth.saturating_sub(1))))
+        .collect();
+    let headcount = reports.iter().map(|report| report.headcount + 1).sum();
+
+    ReportNode {
+        empno: row.empno,
+        ename: row.ename,
+        job: row.job,
+        level: row.level,
+        headcount,
+        reports: match depth {
+            Some(0) => Vec::new(),
+            _ => reports,
+        },
+    }
+}
+
+/// 上司の連鎖取得
+///
+/// get: /emp/{empno}/chain
+/// 直属の上司から最上位（mgr が null）の社員までを返却する.
+#[get("/emp/{empno}/chain")]
+async fn get_emp_chain(
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let empno: i32 = path?.into_inner().try_into().unwrap();
+
+    // 上司の連鎖を取得する
+    let chain = find_chain(&data.conn, empno).await?;
+    if chain.is_empty() {
+        return Err(ApiCustomError::NotFound);
+    }
+
+    // 整合性チェック
+    verify_chain(&chain)?;
+
+    // レスポンス（起点の社員は除く）
+    Ok(HttpResponse::Ok().json(&chain[1..]))
+}
+
+/// 上司の連鎖取得（DB）
+///
+/// 起点の社員（level 0）を含む. 起点の社員が存在しない場合は空.
+pub(crate) async fn find_chain<C: ConnectionTrait>(
+    conn: &C,
+    empno: i32,
+) -> Result<Vec<ChainRow>, DbErr> {
+    ChainRow::find_by_statement(Statement::from_sql_and_values(
+        conn.get_database_backend(),
+        CHAIN_SQL,
+        [empno.into()],
+    ))
+    .all(conn)
+    .await
+}
+
+/// 上司の連鎖の整合性チェック
+///
+/// 循環している場合、または存在しない mgr を参照している場合は処理不可
+pub(crate) fn verify_chain(chain: &[ChainRow]) -> Result<(), ApiCustomError> {
+    let last = match chain.last() {
+        Some(last) => last,
+        _ => return Ok(()),
+    };
+    if last.is_cycle {
+        return Err(ApiCustomError::unprocessable(
+            "mgr_cyclic",
+            [("empno", json!(last.empno))],
+        ));
+    }
+    if let Some(mgr) = last.mgr {
+        return Err(ApiCustomError::unprocessable(
+            "mgr_chain_broken",
+            [("mgr", json!(mgr)), ("empno", json!(last.empno))],
+        ));
+    }
+    Ok(())
+}
+
+/// mgr（上司）整合性チェック
+///
+/// * `empno` - 対象の社員（新規登録の場合は None）
+/// * `mgr` - 設定する上司の社員コード
+///
+/// 上司が存在しない場合、自分自身の場合、上司の連鎖に対象の社員が含まれる（循環する）場合は処理不可.
+/// 既存の社員の上司を変更する場合、同時に変更された上司どうしで循環しないよう、チェック前に上司変更を直列化する
+/// （トランザクション内で呼び出すこと. ロックはコミット・ロールバックまで保持する）
+pub(crate) async fn verify_mgr<C: ConnectionTrait>(
+    conn: &C,
+    empno: Option<i32>,
+    mgr: i32,
+) -> Result<(), ApiCustomError> {
+    // 自己参照
+    if empno == Some(mgr) {
+        return Err(ApiCustomError::unprocessable(
+            "mgr_self",
+            [("mgr", json!(mgr))],
+        ));
+    }
+
+    // 排他（上司変更の直列化）
+    if empno.is_some() {
+        conn.execute(Statement::from_sql_and_values(
+            conn.get_database_backend(),
+            MGR_LOCK_SQL,
+            [MGR_LOCK_KEY.into()],
+        ))
+        .await?;
+    }
+
+    // 存在チェック
+    let chain = find_chain(conn, mgr).await?;
+    if chain.is_empty() {
+        return Err(ApiCustomError::unprocessable(
+            "mgr_not_found",
+            [("mgr", json!(mgr))],
+        ));
+    }
+
+    // 循環チェック
+    if let Some(empno) = empno {
+        if chain.iter().any(|row| row.empno == empno) {
+            return Err(ApiCustomError::unprocessable(
+                "mgr_cycle",
+                [("mgr", json!(mgr)), ("empno", json!(empno))],
+            ));
+        }
+    }
+
+    // 既存データの整合性チェック
+    verify_chain(&chain)
+}
@@ -0,0 +1,111 @@
+use actix_web::{get, web, HttpResponse};
+use sea_orm::prelude::Decimal;
+use sea_orm::sea_query::{Expr, Func, IntoCondition, SimpleExpr};
+use sea_orm::*;
+
+use crate::entities::ext::ActiveFilter;
+use crate::entities::prelude::{Dept, Emp};
+use crate::entities::{dept, emp};
+use crate::error::ApiCustomError;
+use crate::state::AppState;
+
+/// 構造体: 部門サマリ（人数・給与）
+#[derive(FromQueryResult, serde::Serialize, Debug)]
+struct DeptSummary {
+    deptno: i32,
+    dname: String,
+    loc: String,
+    // 人数
+    headcount: i64,
+    // sal 合計
+    total_sal: Decimal,
+    // sal 平均（社員がいない場合は null）
+    avg_sal: Option<Decimal>,
+    // sal 最小（社員がいない場合は null）
+    min_sal: Option<Decimal>,
+    // sal 最大（社員がいない場合は null）
+    max_sal: Option<Decimal>,
+    // comm 合計
+    total_comm: Decimal,
+    // 報酬合計（sal + comm）
+    total_compensation: Decimal,
+}
+
+/// 部門サマリ 全件取得
+#[get("/reports/dept-summary")]
+async fn get_dept_summary_all(data: web::Data<AppState>) -> Result<HttpResponse, ApiCustomError> {
+    // 集計
+    let summaries = dept_summary_select()
+        .into_model::<DeptSummary>()
+        .all(&data.conn)
+        .await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok().json(summaries))
+}
+
+/// 部門サマリ キー取得
+#[get("/dept/{deptno}/summary")]
+async fn get_dept_summary_by_key(
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let deptno: i32 = path?.into_inner().try_into().unwrap();
+
+    // 集計
+    let summary = dept_summary_select()
+        .filter(dept::Column::Deptno.eq(deptno))
+        .into_model::<DeptSummary>()
+        .one(&data.conn)
+        .await?;
+
+    // レスポンス
+    match summary {
+        Some(summary) => Ok(HttpResponse::Ok().json(summary)),
+        _ => Err(ApiCustomError::NotFound),
+    }
+}
+
+/// 部門サマリ 集計クエリ
+///
+/// dept に emp を外部結合して deptno 単位で集計する（社員がいない部門も0件として含む）.
+/// 論理削除された部門・社員は含まない.
+fn dept_summary_select() -> Select<dept::Entity> {
+    let sal = || Expr::col((Emp, emp::Column::Sal));
+    let comm = || coalesce_zero(Expr::col((Emp, emp::Column::Comm)).into());
+
+    Dept::find_active()
+        .select_only()
+        .column(dept::Column::Deptno)
+        .column(dept::Column::Dname)
+        .column(dept::Column::Loc)
+        .column_as(Expr::col((Emp, emp::Column::Empno)).count(), "headcount")
+        .column_as(coalesce_zero(sal().sum()), "total_sal")
+        .column_as(
+            SimpleExpr::from(Func::round_with_precision(Func::avg(sal()), 2)),
+            "avg_sal",
+        )
+        .column_as(sal().min(), "min_sal")
+        .column_as(sal().max(), "max_sal")
+        .column_as(coalesce_zero(Expr::expr(comm()).sum()), "total_comm")
+        .column_as(
+            coalesce_zero(Expr::expr(sal().add(comm())).sum()),
+            "total_compensation",
+        )
+        .join(
+            JoinType::LeftJoin,
+            dept::Relation::Emp.def().on_condition(|_, right| {
+                Expr::col((right, emp::Column::DeletedAt))
+                    .is_null()
+                    .into_condition()
+            }),
+        )
+        .group_by(dept::Column::Deptno)
+        .order_by_asc(dept::Column::Deptno)
+}
+
+/// null の場合は 0
+fn coalesce_zero(expr: SimpleExpr) -> SimpleExpr {
+    Func::coalesce([expr, Expr::val(Decimal::ZERO).into()]).into()
+}
@@ -0,0 +1,223 @@
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse};
+use sea_orm::prelude::{DateTimeWithTimeZone, Decimal};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde_json::json;
+use std::collections::HashMap;
+use validator::Validate;
+
+use crate::entities::ext::AsOfFilter;
+use crate::entities::prelude::{Emp, Salgrade};
+use crate::entities::{emp, salgrade};
+use crate::error::ApiCustomError;
+use crate::pagination::{fetch_page, page_response, PageQuery};
+use crate::state::AppState;
+
+/// 構造体: salgrade リクエストJson
+#[derive(validator::Validate, serde::Deserialize, serde::Serialize, Debug)]
+#[validate(schema(function = "validate_salgrade_request"))]
+struct SalgradeRequestJson {
+    // losal
+    #[validate(range(min = 0.0, max = 99999.99))]
+    losal: f32,
+
+    // hisal
+    #[validate(range(min = 0.0, max = 99999.99))]
+    hisal: f32,
+}
+
+/// losal <= hisal であること
+fn validate_salgrade_request(form: &SalgradeRequestJson) -> Result<(), validator::ValidationError> {
+    if form.losal > form.hisal {
+        return Err(validator::ValidationError::new("salgrade_range"));
+    }
+    Ok(())
+}
+
+/// salgrade 一覧取得（ページング）
+#[get("/salgrade")]
+async fn get_salgrade_all(
+    req: HttpRequest,
+    query: Result<actix_web::web::Query<PageQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋バリデート）
+    let query = query?.into_inner();
+    query.validate()?;
+
+    // レコード取得（ページング）
+    let salgrades = fetch_page(
+        &data.conn,
+        Salgrade::find(),
+        salgrade::Column::Grade,
+        &query,
+    )
+    .await?;
+
+    // レスポンス
+    Ok(page_response(&req, &salgrades))
+}
+
+/// salgrade キー取得
+#[get("/salgrade/{grade}")]
+async fn get_salgrade_by_key(
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋型チェックと型変換）
+    let grade: i32 = path?.into_inner().try_into().unwrap();
+
+    // キーに該当するレコードを取得する
+    let salgrade = Salgrade::find_by_id(grade).one(&data.conn).await?;
+
+    // レスポンス
+    match salgrade {
+        Some(salgrade) => Ok(HttpResponse::Ok().json(salgrade)),
+        _ => Err(ApiCustomError::NotFound),
+    }
+}
+
+/// salgrade 登録
+#[post("/salgrade")]
+async fn post_salgrade(
+    form: Result<actix_web::web::Json<SalgradeRequestJson>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // バリデート
+    let form = form?.into_inner();
+    form.validate()?;
+
+    // DB登録（範囲重複チェック込. トランザクション）
+    let txn = data.conn.begin().await?;
+    verify_no_overlap(&txn, None, &form).await?;
+    let salgrade = salgrade::ActiveModel::from_json(json!(form))?
+        .insert(&txn)
+        .await?;
+    txn.commit().await?;
+
+    // レスポンス
+    Ok(HttpResponse::Created().json(salgrade))
+}
+
+/// salgrade 変更
+#[patch("/salgrade/{grade}")]
+async fn patch_salgrade(
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    form: Result<actix_web::web::Json<SalgradeRequestJson>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（型チェック込）
+    let grade: i32 = path?.into_inner().try_into().unwrap();
+
+    // バリデート
+    let form = form?;
+    form.validate()?;
+
+    // 更新（範囲重複チェック込. トランザクション）
+    let txn = data.conn.begin().await?;
+    let salgrade = Salgrade::find_by_id(grade).one(&txn).await?;
+    let updated_salgrade = match salgrade {
+        Some(salgrade) => {
+            verify_no_overlap(&txn, Some(grade), &form).await?;
+            let mut salgrade_active_model = salgrade.into_active_model();
+            salgrade_active_model.set_from_json(json!(form))?;
+            salgrade_active_model.update(&txn).await?
+        }
+        _ => return Err(ApiCustomError::NotFound),
+    };
+    txn.commit().await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok().json(updated_salgrade))
+}
+
+/// salgrade 削除
+#[delete("/salgrade/{grade}")]
+async fn delete_salgrade(
+    path: Result<actix_web::web::Path<u32>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（型チェック込）
+    let grade: i32 = path?.into_inner().try_into().unwrap();
+
+    // キーに該当するレコードを削除する
+    let result = Salgrade::delete_by_id(grade).exec(&data.conn).await?;
+
+    // レスポンス
+    if result.rows_affected == 0 {
+        return Err(ApiCustomError::NotFound);
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 範囲重複チェック
+///
+/// 他の等級と losal ~ hisal の範囲が重なる場合は処理不可.
+/// 同時に登録・更新された場合の重複は排他制約 ex_salgrade_range で検出する（409）
+async fn verify_no_overlap<C: ConnectionTrait>(
+    conn: &C,
+    grade: Option<i32>,
+    form: &SalgradeRequestJson,
+) -> Result<(), ApiCustomError> {
+    let overlapped = Salgrade::find()
+        .filter(salgrade::Column::Losal.lte(to_decimal(form.hisal)))
+        .filter(salgrade::Column::Hisal.gte(to_decimal(form.losal)))
+        .filter(Condition::all().add_option(grade.map(|grade| salgrade::Column::Grade.ne(grade))))
+        .one(conn)
+        .await?;
+
+    match overlapped {
+        Some(overlapped) => Err(ApiCustomError::unprocessable(
+            "salgrade_overlap",
+            [
+                ("losal", json!(form.losal.to_string())),
+                ("hisal", json!(form.hisal.to_string())),
+                ("grade", json!(overlapped.grade)),
+            ],
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// f32 -> Decimal（小数点以下2桁）
+fn to_decimal(value: f32) -> Decimal {
+    Decimal::from_f32_retain(value)
+        .unwrap_or_default()
+        .round_dp(2)
+}
+
+/// 等級取得
+///
+/// emp.sal を salgrade.losal ~ hisal の範囲で結合し、empno -> grade を返却する.
+/// as_of 指定時は as_of 時点の emp（履歴）と結合する（過去の sal で判定する）.
+/// どの等級にも該当しない emp は含まない.
+pub(crate) async fn find_grades<C: ConnectionTrait>(
+    conn: &C,
+    as_of: Option<DateTimeWithTimeZone>,
+    empnos: Vec<i32>,
+) -> Result<HashMap<i32, i32>, DbErr> {
+    let select = match as_of {
+        Some(as_of) => Emp::find_as_of(as_of),
+        None => Emp::find(),
+    };
+    let mut select = select
+        .select_only()
+        .column(emp::Column::Empno)
+        .column(salgrade::Column::Grade)
+        .filter(emp::Column::Empno.is_in(empnos));
+    QueryTrait::query(&mut select).join(
+        JoinType::InnerJoin,
+        Salgrade,
+        Expr::col((Emp, emp::Column::Sal)).between(
+            Expr::col((Salgrade, salgrade::Column::Losal)),
+            Expr::col((Salgrade, salgrade::Column::Hisal)),
+        ),
+    );
+
+    Ok(select
+        .into_tuple::<(i32, i32)>()
+        .all(conn)
+        .await?
+        .into_iter()
+        .collect())
+}
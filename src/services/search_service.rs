@@ -0,0 +1,204 @@
+use actix_web::{get, web, HttpResponse};
+use sea_orm::*;
+use validator::Validate;
+
+use crate::error::ApiCustomError;
+use crate::state::AppState;
+
+/// 横断検索SQL（emp: ename・job / dept: dname・loc）
+///
+/// * `$1` - 検索文字列
+/// * `$2` - 前方一致の tsquery（単語ごとに `:*`）
+/// * `$3` - 前方一致の LIKE パターン（小文字）
+/// * `$4` - 取得件数
+///
+/// 前方一致（tsvector・LIKE）または類似（pg_trgm）するレコードを、類似度の高い順に返却する（前方一致を優先）.
+/// 論理削除されたレコードは含まない.
+const SEARCH_SQL: &str = r#"
+SELECT entity_type, key, name, detail, rank FROM (
+    SELECT 'emp' AS entity_type, empno AS key, ename AS name, job AS detail,
+        (GREATEST(
+            similarity(lower(ename), lower($1)),
+            similarity(lower(job), lower($1)),
+            ts_rank(to_tsvector('simple', ename || ' ' || job), to_tsquery('simple', $2))
+        ) + CASE WHEN lower(ename) LIKE $3 OR lower(job) LIKE $3 THEN 1 ELSE 0 END)::real AS rank
+    FROM emp
+    WHERE deleted_at IS NULL
+      AND (to_tsvector('simple', ename || ' ' || job) @@ to_tsquery('simple', $2)
+        OR lower(ename) LIKE $3 OR lower(job) LIKE $3
+        OR lower(ename) % lower($1) OR lower(job) % lower($1))
+  UNION ALL
+    SELECT 'dept' AS entity_type, deptno AS key, dname AS name, loc AS detail,
+        (GREATEST(
+            similarity(lower(dname), lower($1)),
+            similarity(lower(loc), lower($1)),
+            ts_rank(to_tsvector('simple', dname || ' ' || loc), to_tsquery('simple', $2))
+        ) + CASE WHEN lower(dname) LIKE $3 OR lower(loc) LIKE $3 THEN 1 ELSE 0 END)::real AS rank
+    FROM dept
+    WHERE deleted_at IS NULL
+      AND (to_tsvector('simple', dname || ' ' || loc) @@ to_tsquery('simple', $2)
+        OR lower(dname) LIKE $3 OR lower(loc) LIKE $3
+        OR lower(dname) % lower($1) OR lower(loc) % lower($1))
+) result
+ORDER BY rank DESC, entity_type, key
+LIMIT $4
+"#;
+
+/// 社員名オートコンプリートSQL
+///
+/// * `$1` - 入力文字列
+/// * `$2` - 前方一致の LIKE パターン（小文字）
+/// * `$3` - 取得件数
+///
+/// 前方一致を優先し、類似度（pg_trgm）の高い順に返却する
+const AUTOCOMPLETE_SQL: &str = r#"
+SELECT empno, ename FROM emp
+WHERE deleted_at IS NULL
+  AND (lower(ename) LIKE $2 OR lower(ename) % lower($1))
+ORDER BY lower(ename) LIKE $2 DESC, similarity(lower(ename), lower($1)) DESC, ename, empno
+LIMIT $3
+"#;
+
+/// 取得件数（デフォルト）
+const DEFAULT_LIMIT: u64 = 20;
+
+/// 構造体: 検索クエリ
+#[derive(validator::Validate, serde::Deserialize, Debug)]
+struct SearchQuery {
+    // 検索文字列
+    #[validate(length(min = 1, max = 100))]
+    q: String,
+
+    // 取得件数
+    #[validate(range(min = 1, max = 100))]
+    limit: Option<u64>,
+}
+
+/// 構造体: 検索結果
+#[derive(FromQueryResult, serde::Serialize, Debug)]
+struct SearchRow {
+    // emp / dept
+    #[serde(rename = "type")]
+    entity_type: String,
+    // empno / deptno
+    key: i32,
+    // ename / dname
+    name: String,
+    // job / loc
+    detail: String,
+    // スコア（前方一致は +1）
+    rank: f32,
+}
+
+/// 構造体: オートコンプリート結果
+#[derive(FromQueryResult, serde::Serialize, Debug)]
+struct AutocompleteRow {
+    empno: i32,
+    ename: String,
+}
+
+/// 横断検索（emp・dept）
+#[get("/search")]
+async fn get_search(
+    query: Result<actix_web::web::Query<SearchQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋バリデート）
+    let query = query?.into_inner();
+    query.validate()?;
+
+    // 検索
+    let rows = SearchRow::find_by_statement(Statement::from_sql_and_values(
+        data.conn.get_database_backend(),
+        SEARCH_SQL,
+        [
+            query.q.as_str().into(),
+            prefix_tsquery(&query.q).into(),
+            prefix_pattern(&query.q).into(),
+            (query.limit.unwrap_or(DEFAULT_LIMIT) as i64).into(),
+        ],
+    ))
+    .all(&data.conn)
+    .await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// 社員名オートコンプリート
+#[get("/search/autocomplete")]
+async fn get_search_autocomplete(
+    query: Result<actix_web::web::Query<SearchQuery>, actix_web::Error>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, ApiCustomError> {
+    // クエリストリング取得（＋バリデート）
+    let query = query?.into_inner();
+    query.validate()?;
+
+    // 検索
+    let rows = AutocompleteRow::find_by_statement(Statement::from_sql_and_values(
+        data.conn.get_database_backend(),
+        AUTOCOMPLETE_SQL,
+        [
+            query.q.as_str().into(),
+            prefix_pattern(&query.q).into(),
+            (query.limit.unwrap_or(DEFAULT_LIMIT) as i64).into(),
+        ],
+    ))
+    .all(&data.conn)
+    .await?;
+
+    // レスポンス
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// 前方一致の tsquery
+///
+/// 英数字以外（tsquery の演算子等）は区切りとして扱い、単語ごとに前方一致（`:*`）を AND で結合する
+fn prefix_tsquery(q: &str) -> String {
+    q.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+/// 前方一致の LIKE パターン（小文字. ワイルドカードはエスケープ）
+fn prefix_pattern(q: &str) -> String {
+    let escaped = q
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{}%", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_tsquery_joins_words_with_and() {
+        assert_eq!(prefix_tsquery("Sales Clerk"), "sales:* & clerk:*");
+    }
+
+    #[test]
+    fn prefix_tsquery_drops_operators_and_symbols() {
+        assert_eq!(prefix_tsquery("a&b | !c:* (d)"), "a:* & b:* & c:* & d:*");
+    }
+
+    #[test]
+    fn prefix_tsquery_empty() {
+        assert_eq!(prefix_tsquery("  & | "), "");
+    }
+
+    #[test]
+    fn prefix_pattern_lowercases() {
+        assert_eq!(prefix_pattern("SMI"), "smi%");
+    }
+
+    #[test]
+    fn prefix_pattern_escapes_wildcards() {
+        assert_eq!(prefix_pattern("50%_off\\"), "50\\%\\_off\\\\%");
+    }
+}
@@ -1,7 +1,15 @@
 use sea_orm::DatabaseConnection;
 
+use crate::i18n::Lang;
+
 /// 構造体: ステート
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub conn: DatabaseConnection,
+    // 更新・削除時に If-Match を必須とする（未指定の場合は 428）
+    pub require_if_match: bool,
+    // X-User-Id・X-User-Role を信頼する（前段のゲートウェイ等がヘッダを設定する場合のみ有効にする）
+    pub trust_user_headers: bool,
+    // メッセージのデフォルト言語（Accept-Language が対応していない言語の場合）
+    pub default_lang: Lang,
 }